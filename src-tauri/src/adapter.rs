@@ -0,0 +1,47 @@
+use std::sync::atomic::Ordering;
+use serde::Serialize;
+
+use crate::error::BleError;
+use crate::{lock, AppState};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BleAdapterInfo {
+    pub address: Option<String>,
+    pub name: Option<String>,
+    pub supported_features: Vec<String>,
+    pub is_scanning: bool,
+    pub connected_device_count: u8,
+}
+
+// The plugin does not expose host controller details, so features are derived from the platform backend
+#[cfg(target_os = "android")]
+fn platform_features() -> Vec<String> {
+    vec!["ble_central".to_string(), "android_bluetooth_le".to_string(), "runtime_permissions".to_string()]
+}
+
+#[cfg(target_os = "ios")]
+fn platform_features() -> Vec<String> {
+    vec!["ble_central".to_string(), "core_bluetooth".to_string()]
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn platform_features() -> Vec<String> {
+    vec!["ble_central".to_string(), "btleplug".to_string()]
+}
+
+/*
+    Report adapter details for bug reports.
+    Fails if the BLE handler is unavailable, e.g. when Bluetooth is off or unsupported.
+*/
+#[tauri::command]
+pub async fn get_ble_adapter_info(state: tauri::State<'_, AppState>) -> Result<BleAdapterInfo, BleError> {
+    state.handler()?;
+
+    Ok(BleAdapterInfo {
+        address: None,
+        name: None,
+        supported_features: platform_features(),
+        is_scanning: state.is_scanning.load(Ordering::SeqCst),
+        connected_device_count: u8::from(*lock(&state.is_connected)),
+    })
+}
@@ -0,0 +1,94 @@
+use uuid::Uuid;
+
+use crate::error::BleError;
+use crate::{lock, service_uuid, AppState, KNOWN_CHARACTERISTICS};
+
+/*
+    KNOWN_CHARACTERISTICS with "heartbeat" resolved to the characteristic set by set_heartbeat_characteristic.
+*/
+pub(crate) fn known_characteristics(state: &AppState) -> Vec<(&'static str, Uuid)> {
+    let heartbeat = *lock(&state.heartbeat_characteristic);
+    KNOWN_CHARACTERISTICS
+        .iter()
+        .map(|&(name, uuid)| (name, if name == "heartbeat" { heartbeat } else { uuid }))
+        .collect()
+}
+
+/*
+    Resolve a characteristic alias. Every name of KNOWN_CHARACTERISTICS is an alias,
+    "lift" and "status" are shorthands for the primary lifting arm channel and the controller status.
+*/
+pub(crate) fn characteristic_by_alias(state: &AppState, alias: &str) -> Result<Uuid, BleError> {
+    let alias = alias.to_lowercase();
+    let alias = match alias.as_str() {
+        "lift" => "lift_a",
+        "status" => "controller_usable",
+        other => other,
+    };
+    known_characteristics(state)
+        .into_iter()
+        .find(|(name, _)| *name == alias)
+        .map(|(_, uuid)| uuid)
+        .ok_or_else(|| BleError::InvalidArgument(format!("Unknown characteristic alias: {}", alias)))
+}
+
+/*
+    Characteristic given either by alias or by UUID.
+*/
+pub(crate) fn resolve_characteristic(state: &AppState, value: &str) -> Result<Uuid, BleError> {
+    characteristic_by_alias(state, value).or_else(|_| crate::subscription::parse_uuid(value))
+}
+
+/*
+    Alias of a known characteristic for logs and error messages, the UUID otherwise.
+*/
+pub(crate) fn characteristic_label(uuid: Uuid) -> String {
+    KNOWN_CHARACTERISTICS
+        .iter()
+        .find(|(_, known)| *known == uuid)
+        .map_or_else(|| uuid.to_string(), |(name, _)| name.to_string())
+}
+
+#[tauri::command]
+pub async fn get_characteristic_by_alias(state: tauri::State<'_, AppState>, alias: String) -> Result<Uuid, BleError> {
+    characteristic_by_alias(&state, &alias)
+}
+
+#[tauri::command]
+pub async fn read_characteristic_by_alias(state: tauri::State<'_, AppState>, alias: String) -> Result<Vec<u8>, BleError> {
+    let uuid = characteristic_by_alias(&state, &alias)?;
+    let service = service_uuid(&state);
+    Ok(crate::receive_data(state, uuid, service).await?)
+}
+
+#[tauri::command]
+pub async fn write_characteristic_by_alias(state: tauri::State<'_, AppState>, alias: String, data: Vec<u8>) -> Result<String, BleError> {
+    let uuid = characteristic_by_alias(&state, &alias)?;
+    let service = service_uuid(&state);
+    Ok(crate::write_data(state, uuid, service, data).await?)
+}
+
+/*
+    Position of `alias` in a write order, characteristics not listed go last.
+*/
+pub(crate) fn write_rank(order: &[String], alias: &str) -> usize {
+    order.iter().position(|entry| entry == alias).unwrap_or(order.len())
+}
+
+/*
+    Write the listed characteristics in this order when several are written together, e.g. ["r_axis", "x_axis"].
+    An empty order writes them concurrently.
+*/
+#[tauri::command]
+pub async fn set_write_order(state: tauri::State<'_, AppState>, order: Vec<String>) -> Result<(), BleError> {
+    let mut canonical: Vec<String> = Vec::with_capacity(order.len());
+    for alias in &order {
+        let label = characteristic_label(characteristic_by_alias(&state, alias)?);
+        if canonical.contains(&label) {
+            return Err(BleError::InvalidArgument(format!("Characteristic {} is listed twice", label)));
+        }
+        canonical.push(label);
+    }
+    *lock(&state.write_order) = canonical;
+    Ok(())
+}
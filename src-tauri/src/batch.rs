@@ -0,0 +1,92 @@
+use std::time::Duration;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::events::report;
+use crate::subscription::parse_uuid;
+use crate::{ensure_connected, ensure_writes_allowed, lock, service_uuid, AppState};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AtomicWrite {
+    pub char_uuid: String,
+    pub data: Vec<u8>,
+}
+
+/*
+    Whether the connected device accepts ATT Prepare Write / Execute Write, cached per connection.
+    tauri-plugin-blec has no queued write API, so this is always false for now.
+*/
+fn supports_queued_writes(state: &AppState) -> bool {
+    *lock(&state.supports_queued_writes).get_or_insert(false)
+}
+
+/*
+    Write several characteristics as one unit.
+    Uses queued writes when supported, otherwise falls back to sequential writes which stop at the
+    first failure without rolling back the writes before it.
+*/
+#[tauri::command]
+pub async fn write_characteristics_atomic(state: tauri::State<'_, AppState>, writes: Vec<AtomicWrite>) -> Result<(), BleError> {
+    ensure_connected(&state).await?;
+    ensure_writes_allowed(&state)?;
+    crate::firmware::ensure_feature_compatible(&state, "batch_write")?;
+
+    let service = service_uuid(&state);
+    let writes = writes
+        .into_iter()
+        .map(|write| Ok((parse_uuid(&write.char_uuid)?, write.data)))
+        .collect::<Result<Vec<_>, BleError>>()?;
+
+    if !supports_queued_writes(&state) {
+        info!("Queued writes unsupported, writing {} characteristics sequentially", writes.len());
+    }
+    for (char_uuid, data) in writes {
+        crate::write_data(state.clone(), char_uuid, service, data).await?;
+    }
+    Ok(())
+}
+
+/*
+    Compare-and-swap write: read the characteristic and write `new_value` only if it still holds `expected`.
+    Returns false without writing when the current value differs.
+    ! The read and the write are separate ATT operations, a change between them is not detected.
+*/
+#[tauri::command]
+pub async fn write_characteristic_safe(state: tauri::State<'_, AppState>, char_uuid: String, service_uuid: String, expected: Vec<u8>, new_value: Vec<u8>) -> Result<bool, BleError> {
+    let char_uuid = parse_uuid(&char_uuid)?;
+    let service = parse_uuid(&service_uuid)?;
+    ensure_writes_allowed(&state)?;
+
+    let current = crate::receive_data(state.clone(), char_uuid, service).await?;
+    if current != expected {
+        info!("Safe write to {} skipped, expected {:02X?} but found {:02X?}", char_uuid, expected, current);
+        return Ok(false);
+    }
+    crate::write_data(state, char_uuid, service, new_value).await?;
+    Ok(true)
+}
+
+/*
+    Write `data`, wait `verify_delay_ms` and read it back, repeating until the read matches or `max_attempts` is reached.
+    For devices that may drop commands silently. Returns the number of attempts used.
+*/
+#[tauri::command]
+pub async fn write_and_verify(state: tauri::State<'_, AppState>, char_uuid: String, service_uuid: String, data: Vec<u8>, max_attempts: u8, verify_delay_ms: u64) -> Result<u8, BleError> {
+    if max_attempts == 0 {
+        return Err(BleError::InvalidArgument("max_attempts must be greater than 0".to_string()));
+    }
+    let char_uuid = parse_uuid(&char_uuid)?;
+    let service = parse_uuid(&service_uuid)?;
+
+    for attempt in 1..=max_attempts {
+        crate::write_data(state.clone(), char_uuid, service, data.clone()).await?;
+        tokio::time::sleep(Duration::from_millis(verify_delay_ms)).await;
+        let current = crate::receive_data(state.clone(), char_uuid, service).await?;
+        if current == data {
+            return Ok(attempt);
+        }
+        info!("Verify of {} failed on attempt {}/{}: read {:02X?}", char_uuid, attempt, max_attempts, current);
+    }
+    Err(report(BleError::VerificationExhausted { characteristic: char_uuid.to_string(), attempts: max_attempts }))
+}
@@ -0,0 +1,64 @@
+use std::sync::atomic::Ordering;
+use serde::Serialize;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::error::BleError;
+use crate::{events, lock, AppState};
+
+// Standard Battery Service (0x180F) and Battery Level characteristic (0x2A19)
+const BATTERY_SERVICE_UUID: Uuid = Uuid::from_bytes([0x00, 0x00, 0x18, 0x0F, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0x80, 0x5F, 0x9B, 0x34, 0xFB]);
+const BATTERY_LEVEL_CHARACTERISTIC_UUID: Uuid = Uuid::from_bytes([0x00, 0x00, 0x2A, 0x19, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0x80, 0x5F, 0x9B, 0x34, 0xFB]);
+pub const DEFAULT_LOW_BATTERY_THRESHOLD: u8 = 20;
+// Largest X/Y joystick magnitude while low battery mode is active
+const LOW_BATTERY_MAX_MAGNITUDE: f32 = 0.5;
+
+#[derive(Debug, Clone, Serialize)]
+struct LowBatteryThrottle {
+    enabled: bool,
+    battery_level: u8,
+}
+
+fn update_low_battery_mode(state: &AppState, battery_level: u8) {
+    let enabled = battery_level < *lock(&state.low_battery_threshold);
+    if state.low_battery_mode.swap(enabled, Ordering::SeqCst) != enabled {
+        info!("Low battery mode {} at {}%", if enabled { "enabled" } else { "disabled" }, battery_level);
+        events::emit("ble://low-battery-throttle", LowBatteryThrottle { enabled, battery_level });
+    }
+}
+
+/*
+    Scale X/Y down to LOW_BATTERY_MAX_MAGNITUDE while low battery mode is active, keeping the direction.
+*/
+pub(crate) fn limit_for_battery(state: &AppState, x: f32, y: f32) -> (f32, f32) {
+    if !state.low_battery_mode.load(Ordering::SeqCst) {
+        return (x, y);
+    }
+    let magnitude = x.hypot(y);
+    if magnitude <= LOW_BATTERY_MAX_MAGNITUDE {
+        return (x, y);
+    }
+    let scale = LOW_BATTERY_MAX_MAGNITUDE / magnitude;
+    (x * scale, y * scale)
+}
+
+/*
+    Read the battery level in percent from the standard Battery Service.
+    Switches low battery mode on or off depending on the low battery threshold.
+*/
+#[tauri::command]
+pub async fn get_battery_level(state: tauri::State<'_, AppState>) -> Result<u8, BleError> {
+    let data = crate::receive_data(state.clone(), BATTERY_LEVEL_CHARACTERISTIC_UUID, BATTERY_SERVICE_UUID).await?;
+    let battery_level = *data.first().ok_or_else(|| BleError::InvalidPayload("Empty battery level".to_string()))?;
+    update_low_battery_mode(&state, battery_level);
+    Ok(battery_level)
+}
+
+#[tauri::command]
+pub async fn set_low_battery_threshold(state: tauri::State<'_, AppState>, percent: u8) -> Result<String, String> {
+    if percent > 100 {
+        return Err(format!("Threshold must be between 0 and 100, got {}", percent));
+    }
+    *lock(&state.low_battery_threshold) = percent;
+    Ok(format!("Low battery threshold set to: {}%", percent))
+}
@@ -0,0 +1,123 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::{lock, AppState};
+
+// Claw slider values for a fully closed and fully open claw
+pub(crate) const CLAW_GRAB_VALUE: u8 = 0xFF;
+pub(crate) const CLAW_RELEASE_VALUE: u8 = 0x00;
+pub const DEFAULT_CLAW_HOLD_MS: u64 = 1000;
+
+/*
+    How activate_claw / deactivate_claw drive the claw.
+    Toggle: every activation flips between grab and release. Momentary: grab on activate, release on deactivate.
+    HoldToClose: grab on activate and release automatically after `hold_duration_ms`.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClawMode {
+    Toggle,
+    Momentary,
+    HoldToClose,
+}
+
+impl ClawMode {
+    fn parse(value: &str) -> Result<Self, BleError> {
+        match value {
+            "toggle" => Ok(Self::Toggle),
+            "momentary" => Ok(Self::Momentary),
+            "hold_to_close" => Ok(Self::HoldToClose),
+            _ => Err(BleError::InvalidArgument(format!("Unsupported claw mode: {}", value))),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Toggle => "toggle",
+            Self::Momentary => "momentary",
+            Self::HoldToClose => "hold_to_close",
+        }
+    }
+}
+
+fn cancel_auto_release(state: &AppState) {
+    if let Some(task) = lock(&state.claw_release_task).take() {
+        task.abort();
+    }
+}
+
+/*
+    Close or open the claw and remember its state for toggle mode.
+*/
+pub(crate) async fn set_claw_closed(state: tauri::State<'_, AppState>, closed: bool) -> Result<(), BleError> {
+    let value = if closed { CLAW_GRAB_VALUE } else { CLAW_RELEASE_VALUE };
+    crate::send_lifting_arm_value(state.clone(), "Claw".to_string(), value).await?;
+    *lock(&state.claw_closed) = closed;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn activate_claw(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    let mode = *lock(&state.claw_mode);
+    match mode {
+        ClawMode::Toggle => {
+            let closed = *lock(&state.claw_closed);
+            set_claw_closed(state, !closed).await
+        }
+        ClawMode::Momentary => set_claw_closed(state, true).await,
+        ClawMode::HoldToClose => {
+            cancel_auto_release(&state);
+            set_claw_closed(state.clone(), true).await?;
+
+            let hold_ms = *lock(&state.claw_hold_duration_ms);
+            let handle = tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(hold_ms)).await;
+                if let Err(e) = set_claw_closed(app.state::<AppState>(), false).await {
+                    info!("Claw auto release failed: {}", e);
+                }
+            });
+            crate::tasks::track(&state, "claw_release", &handle);
+            if let Some(previous) = lock(&state.claw_release_task).replace(handle) {
+                previous.abort();
+            }
+            Ok(())
+        }
+    }
+}
+
+/*
+    Release the claw in momentary and hold to close mode. No-op in toggle mode.
+*/
+#[tauri::command]
+pub async fn deactivate_claw(state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    let mode = *lock(&state.claw_mode);
+    match mode {
+        ClawMode::Toggle => Ok(()),
+        ClawMode::Momentary => set_claw_closed(state, false).await,
+        ClawMode::HoldToClose => {
+            cancel_auto_release(&state);
+            set_claw_closed(state, false).await
+        }
+    }
+}
+
+/*
+    mode: "toggle", "momentary" or "hold_to_close". `hold_duration_ms` is used by hold to close mode.
+*/
+#[tauri::command]
+pub async fn set_claw_mode(state: tauri::State<'_, AppState>, mode: String, hold_duration_ms: Option<u64>) -> Result<String, BleError> {
+    let mode = ClawMode::parse(&mode)?;
+    let hold_duration_ms = hold_duration_ms.unwrap_or(DEFAULT_CLAW_HOLD_MS);
+    cancel_auto_release(&state);
+    *lock(&state.claw_mode) = mode;
+    *lock(&state.claw_hold_duration_ms) = hold_duration_ms;
+    Ok(format!("Claw mode set to: {} (hold {} ms)", mode.as_str(), hold_duration_ms))
+}
+
+#[tauri::command]
+pub async fn get_claw_mode(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    Ok(lock(&state.claw_mode).as_str().to_string())
+}
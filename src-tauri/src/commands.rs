@@ -0,0 +1,193 @@
+use serde::Serialize;
+
+/*
+    Static description of every registered command so the frontend can discover them.
+    Parameter names are given as the frontend passes them to invoke() (camelCase).
+    Keep in sync with generate_handler! in run().
+*/
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ParamInfo {
+    pub name: &'static str,
+    pub type_name: &'static str,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CommandInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub parameters: &'static [ParamInfo],
+    pub return_type: &'static str,
+}
+
+const fn param(name: &'static str, type_name: &'static str) -> ParamInfo {
+    ParamInfo { name, type_name, required: true }
+}
+
+const fn optional(name: &'static str, type_name: &'static str) -> ParamInfo {
+    ParamInfo { name, type_name, required: false }
+}
+
+const fn command(name: &'static str, description: &'static str, parameters: &'static [ParamInfo], return_type: &'static str) -> CommandInfo {
+    CommandInfo { name, description, parameters, return_type }
+}
+
+pub static COMMAND_REGISTRY: &[CommandInfo] = &[
+    command("set_connected_device_address", "Set or clear (empty string) the connected device address.", &[param("address", "String")], "String"),
+    command("get_connected_device_address", "Get the connected device address.", &[], "String"),
+    command("get_controller_usable", "Get the cached controller usable state.", &[], "bool"),
+    command("set_axis_encoding", "Configure byte width and order of a joystick axis (0 = X, 1 = Y, 2 = R).", &[param("axis", "u8"), param("width", "u8"), param("endian", "String")], "String"),
+    command("set_data_endianness", "Set byte order (\"little\" or \"big\") of characteristic data and all joystick axes, overriding per-axis byte order.", &[param("endian", "String")], "String"),
+    command("set_service_uuid", "Use another service UUID for all reads and writes, e.g. for test firmware.", &[param("uuid", "String")], "String"),
+    command("reset_service_uuid", "Restore the default service UUID.", &[], "String"),
+    command("poll_controller_status", "Read the controller usable state from the device.", &[], "bool"),
+    command("read_controller_status_raw", "Read the controller status bytes without interpreting them.", &[], "Vec<u8>"),
+    command("send_joystick_data", "Send joystick X, Y and R bytes, 0x7F is center.", &[param("x", "u8"), param("y", "u8"), param("r", "u8")], "String"),
+    command("send_lifting_arm_value", "Send a value to a lifting arm channel (A, B, C, END, Claw).", &[param("channel", "String"), param("value", "u8")], "String"),
+    command("set_lifting_arm_range", "Set the software safety range for lifting arm values.", &[param("min", "u8"), param("max", "u8")], "String"),
+    command("set_workspace_limits", "Set the safe range of X, Y, R and the lifting arm.", &[param("limits", "WorkspaceLimits")], "String"),
+    command("get_workspace_limits", "Get the workspace limits.", &[], "WorkspaceLimits"),
+    command("set_workspace_enforcement", "Clamp joystick and lifting arm values to the workspace limits.", &[param("enabled", "bool")], "String"),
+    command("send_arm_command", "Send a named arm command.", &[param("command", "String")], "String"),
+    command("emergency_stop", "Center all joystick axes, always written with response.", &[], "String"),
+    command("get_arm_data", "Read X, Y and R back from the device.", &[], "ArmData"),
+    command("format_arm_data", "Format ArmData as a human readable string.", &[param("data", "ArmData")], "String"),
+    command("set_default_write_type", "Set the write type used for BLE writes (with_response, without_response).", &[param("writeType", "String")], "String"),
+    command("set_characteristic_write_type", "Override the write type of one characteristic, omit writeType to remove the override.", &[param("charUuid", "String"), optional("writeType", "String")], "String"),
+    command("get_write_type", "Get the write type used for a characteristic.", &[param("charUuid", "String")], "String"),
+    command("pause_writes", "Reject all writes except emergency_stop until resume_writes.", &[], "String"),
+    command("resume_writes", "Allow writes again after pause_writes.", &[], "String"),
+    command("set_scan_mode", "Set scan mode (low_power, balanced, high_duty).", &[param("mode", "String")], "String"),
+    command("preload_operation", "Scan for the default device and connect when found.", &[], "()"),
+    command("check_ble_permissions", "Check and request Bluetooth permissions.", &[], "bool"),
+    command("disconnect", "Center the joystick and disconnect.", &[], "String"),
+    command("connect", "Scan for and connect to the given address.", &[param("addr", "String")], "String"),
+    command("run_script", "Run a JSON arm script file.", &[param("path", "String")], "ScriptResult"),
+    command("stop_script", "Stop the running script before its next step.", &[], "String"),
+    command("get_script_progress", "Get progress of the running script.", &[], "ScriptProgress"),
+    command("set_on_ready_script", "Set script steps run by preload_operation once the controller is usable.", &[param("steps", "Vec<ScriptStep>")], "String"),
+    command("clear_on_ready_script", "Remove the on ready script.", &[], "String"),
+    command("send_arm_sequence", "Run arm commands in order with optional delays before and after each.", &[param("steps", "Vec<ArmSequenceStep>")], "()"),
+    command("cancel_arm_sequence", "Cancel the running arm sequence before its next step.", &[], "String"),
+    command("send_arm_command_confirmed", "Run an arm command and poll a characteristic until it echoes the expected value.", &[param("command", "ArmCommand"), param("confirmationChar", "String"), param("expected", "Vec<u8>"), param("timeoutMs", "u64")], "()"),
+    command("activate_claw", "Press the claw button according to the claw mode.", &[], "()"),
+    command("deactivate_claw", "Release the claw button, opens the claw in momentary and hold to close mode.", &[], "()"),
+    command("set_claw_mode", "Set the claw mode: toggle, momentary or hold_to_close.", &[param("mode", "String"), optional("holdDurationMs", "u64")], "String"),
+    command("get_claw_mode", "Get the claw mode.", &[], "String"),
+    command("save_device_profile", "Save the current settings as a named profile.", &[param("profileName", "String")], "()"),
+    command("load_device_profile", "Restore settings from a named profile.", &[param("profileName", "String")], "()"),
+    command("list_device_profiles", "List saved profile names.", &[], "Vec<String>"),
+    command("export_device_profile", "Export UUIDs and settings as a shareable profile JSON.", &[], "String"),
+    command("import_device_profile", "Validate and apply a profile JSON from export_device_profile.", &[param("json", "String")], "()"),
+    command("probe_device", "Compare the device characteristics with the known UUIDs.", &[], "ProbeResult"),
+    command("verify_device_identity", "Check that the connected device exposes the active service UUID, disconnect it otherwise.", &[], "()"),
+    command("move_home", "Move all axes to the home position.", &[], "()"),
+    command("set_home_position", "Configure the home position.", &[param("x", "u8"), param("y", "u8"), param("r", "u8"), param("liftingArm", "u8")], "String"),
+    command("get_home_position", "Get the home position.", &[], "ArmPosition"),
+    command("save_home_as_current", "Use the last sent arm state as home position.", &[], "ArmPosition"),
+    command("transition_lifting_arm", "Move the primary lifting arm between two values in 20 ms steps.", &[param("from", "u8"), param("to", "u8"), param("durationMs", "u64")], "()"),
+    command("calibrate_arm_range", "Drive an axis to both extremes and read back the positions it reaches.", &[param("axis", "String"), param("durationPerDirectionMs", "u64")], "ArmRangeCalibration"),
+    command("get_session_history", "Get the last N session statistics.", &[param("lastN", "usize")], "Vec<SessionStats>"),
+    command("get_uptime_histogram", "Get count, min, max, mean and percentiles of connection uptimes.", &[], "UptimeHistogram"),
+    command("set_log_rotation", "Set how many log files are kept and the size at which they rotate.", &[param("maxLogFiles", "usize"), param("maxLogSizeBytes", "u64")], "String"),
+    command("get_log_info", "Get the log directory, its total size and file count.", &[], "LogInfo"),
+    command("get_metrics", "Write and error counters of the current stats epoch in Prometheus text format.", &[], "String"),
+    command("reset_connection_stats", "Zero the connection counters and start a new stats epoch.", &[], "()"),
+    command("get_connection_stats", "Get counters of the current stats epoch.", &[], "ConnectionStats"),
+    command("subscribe_to_characteristic", "Subscribe to notifications and re-emit them as an event.", &[param("charUuid", "String"), param("serviceUuid", "String"), param("eventName", "String")], "()"),
+    command("subscribe_to_characteristics", "Subscribe to several characteristics, failures do not abort the rest.", &[param("subscriptions", "Vec<SubscriptionRequest>")], "Vec<SubscriptionResult>"),
+    command("unsubscribe_from_characteristic", "Unsubscribe from a characteristic.", &[param("charUuid", "String")], "()"),
+    command("set_auto_resubscribe", "Re-subscribe dropped subscriptions after reconnect.", &[param("enabled", "bool")], "String"),
+    command("set_notification_threshold", "Only emit notifications of a characteristic when a byte changed by more than min_change (0 passes all).", &[param("charUuid", "String"), param("minChange", "u8")], "String"),
+    command("scan_rssi_map", "Scan for a while and report RSSI statistics of every device in range.", &[param("durationMs", "u64")], "Vec<RssiSample>"),
+    command("wait_for_device_discoverable", "Scan until the device with this address shows up or the timeout expires.", &[param("address", "String"), param("timeoutMs", "u64")], "()"),
+    command("multi_scan", "Merge several consecutive scan windows, keeping the best RSSI per device.", &[param("windows", "u8"), param("windowMs", "u64")], "Vec<BleDeviceInfo>"),
+    command("scan_all_meguru_devices", "Find every device advertising the Meguru service, the connected one marked.", &[param("timeoutMs", "u64")], "Vec<BleDeviceInfo>"),
+    command("scan_and_list", "Scan and list nearby devices with registry alias and last connection, sorted by rssi, name or last_seen.", &[param("sortBy", "String"), param("timeoutMs", "u64")], "Vec<EnrichedDevice>"),
+    command("calculate_joystick_polar", "Convert joystick bytes to angle and magnitude.", &[param("x", "u8"), param("y", "u8")], "JoystickPolar"),
+    command("set_joystick_deadzone", "Set the joystick deadzone (0.0 to 1.0), shaped by set_deadband_shape.", &[param("deadzone", "f32")], "String"),
+    command("set_deadband_shape", "Set the X/Y deadband shape: circular or square.", &[param("shape", "String")], "String"),
+    command("get_deadband_shape", "Get the X/Y deadband shape.", &[], "String"),
+    command("set_joystick_curve", "Set the joystick response curve power (0.1 to 4.0, 1.0 is linear).", &[param("power", "f32")], "String"),
+    command("get_joystick_curve", "Get the joystick response curve power.", &[], "f32"),
+    command("set_coalesce_joystick_writes", "Merge joystick frames arriving while a frame is written, only the newest is sent.", &[param("enabled", "bool")], "String"),
+    command("get_battery_level", "Read the battery level in percent and update low battery mode.", &[], "u8"),
+    command("get_firmware_version", "Read the firmware version from the Device Information Service.", &[], "FirmwareVersion"),
+    command("check_feature_compatibility", "Check which features the connected firmware supports.", &[], "CompatibilityReport"),
+    command("set_low_battery_threshold", "Set the battery level below which joystick input is limited to half range.", &[param("percent", "u8")], "String"),
+    command("move_relative", "Move X/Y/R by signed deltas from the last sent state.", &[param("dx", "i8"), param("dy", "i8"), param("dr", "i8")], "()"),
+    command("get_relative_step_size", "Get the step size used by move_left/right/up/down.", &[], "u8"),
+    command("set_relative_step_size", "Set the step size used by move_left/right/up/down.", &[param("step", "u8")], "String"),
+    command("move_left", "Decrease X by the relative step size.", &[], "()"),
+    command("move_right", "Increase X by the relative step size.", &[], "()"),
+    command("move_up", "Increase Y by the relative step size.", &[], "()"),
+    command("move_down", "Decrease Y by the relative step size.", &[], "()"),
+    command("move_in_direction", "Move forward, backward, left, right, rotate_cw, rotate_ccw or stop at speed 0..=127.", &[param("direction", "String"), param("speed", "u8")], "()"),
+    command("set_joystick_target", "Set the joystick position sent by the joystick poll task.", &[param("x", "u8"), param("y", "u8"), param("r", "u8")], "()"),
+    command("start_joystick_poll", "Send the joystick target to the device at a fixed interval.", &[param("intervalMs", "u64")], "String"),
+    command("stop_joystick_poll", "Stop the joystick poll task.", &[], "String"),
+    command("set_r_axis_semantics", "Set R axis interpretation (joystick, angle, signed).", &[param("semantics", "String")], "String"),
+    command("get_r_axis_semantics", "Get R axis interpretation.", &[], "String"),
+    command("send_joystick_normalized", "Send joystick data from normalized floats.", &[param("x", "f32"), param("y", "f32"), param("r", "f32")], "String"),
+    command("send_joystick_i16", "Send joystick data from i16 gamepad axes, 0 is center.", &[param("x", "i16"), param("y", "i16"), param("r", "i16")], "String"),
+    command("enable_controller_status_notification", "Keep the controller status in sync via notifications or polling.", &[], "()"),
+    command("disable_controller_status_notification", "Stop controller status notifications and polling.", &[], "()"),
+    command("set_controller_status_strategy", "Choose notifications or polling for controller status.", &[param("useNotificationIfAvailable", "bool"), optional("pollIntervalMs", "u64")], "String"),
+    command("simulate_controller_not_usable", "Debug builds only: report the controller as not usable for a while.", &[param("durationMs", "u64")], "String"),
+    command("get_ble_adapter_info", "Get BLE adapter details.", &[], "BleAdapterInfo"),
+    command("get_characteristic_by_alias", "Resolve a characteristic alias such as x_axis, lift, claw, status or heartbeat to its UUID.", &[param("alias", "String")], "Uuid"),
+    command("read_characteristic_by_alias", "Read a characteristic by alias.", &[param("alias", "String")], "Vec<u8>"),
+    command("write_characteristic_by_alias", "Write raw data to a characteristic by alias.", &[param("alias", "String"), param("data", "Vec<u8>")], "String"),
+    command("write_characteristics_atomic", "Write several characteristics as one unit, sequentially if queued writes are unsupported.", &[param("writes", "Vec<AtomicWrite>")], "()"),
+    command("write_characteristic_safe", "Write new_value only if the characteristic currently holds expected, returns whether it was written.", &[param("charUuid", "String"), param("serviceUuid", "String"), param("expected", "Vec<u8>"), param("newValue", "Vec<u8>")], "bool"),
+    command("write_and_verify", "Write and read back until the value matches, returns the attempts used.", &[param("charUuid", "String"), param("serviceUuid", "String"), param("data", "Vec<u8>"), param("maxAttempts", "u8"), param("verifyDelayMs", "u64")], "u8"),
+    command("set_write_order", "Write joystick axes one by one in this alias order, an empty list writes them concurrently.", &[param("order", "Vec<String>")], "()"),
+    command("set_connection_mode", "Keep the link up (\"persistent\") or drop it between writes (\"on_demand\"), disconnectAfterMs 1 to 600000.", &[param("mode", "String"), optional("disconnectAfterMs", "u64")], "String"),
+    command("get_connection_mode", "Get the connection mode.", &[], "String"),
+    command("request_connection_parameters", "Request connection interval, slave latency and supervision timeout, repeated after every connect.", &[param("intervalMinMs", "f32"), param("intervalMaxMs", "f32"), param("latency", "u16"), param("supervisionTimeoutMs", "u32")], "ConnectionParamsResult"),
+    command("set_auto_reconnect_on_write_fail", "Reconnect and replay a write when the link drops.", &[param("enabled", "bool")], "String"),
+    command("set_reconnect_strategy", "Set what happens when the link drops: none, auto_once or auto_with_backoff.", &[param("strategy", "ReconnectStrategyConfig")], "String"),
+    command("enable_dev_mode", "Enable dev mode with the build-time password.", &[param("password", "String")], "String"),
+    command("disable_dev_mode", "Disable dev mode.", &[], "String"),
+    command("send_raw_bytes", "Write raw bytes to any characteristic (dev mode).", &[param("charUuid", "String"), param("serviceUuid", "String"), param("data", "Vec<u8>"), param("writeType", "String")], "()"),
+    command("subscribe_all_debug", "Subscribe to every notifiable characteristic as ble://debug/{alias} (dev mode).", &[], "Vec<SubscriptionResult>"),
+    command("unsubscribe_all_debug", "Cancel the subscriptions of subscribe_all_debug (dev mode).", &[], "()"),
+    command("get_last_disconnect_reason", "Get why the last connection ended.", &[], "Option<DisconnectReason>"),
+    command("get_connection_quality", "Get the connection quality score.", &[], "ConnectionQuality"),
+    command("start_quality_monitor", "Emit ble://quality-update periodically.", &[param("intervalMs", "u64")], "String"),
+    command("stop_quality_monitor", "Stop quality updates.", &[], "String"),
+    command("start_heartbeat", "Write 0x00 to the heartbeat characteristic periodically to keep the device awake.", &[param("intervalMs", "u64")], "String"),
+    command("stop_heartbeat", "Stop heartbeat writes.", &[], "String"),
+    command("set_heartbeat_characteristic", "Set the characteristic UUID written by the heartbeat.", &[param("uuid", "String")], "String"),
+    command("set_write_rate_limit", "Limit writes per second (at most 1000), 0 disables the limit.", &[param("writesPerSecond", "u32")], "String"),
+    command("set_adaptive_rate_limit", "Slow writes to a quarter of the limit while RSSI is poor.", &[param("enabled", "bool")], "String"),
+    command("set_joystick_frame_rate", "Drop joystick frames beyond this rate (at most 1000, 0 disables).", &[param("framesPerSecond", "u32")], "String"),
+    command("set_emit_drop_events", "Emit ble://frame-dropped for frames dropped by the joystick frame rate limit.", &[param("enabled", "bool")], "String"),
+    command("get_queue_depth", "Get the number of pending writes and the write queue capacity.", &[], "QueueStatus"),
+    command("get_pending_write_count", "Get the number of write_data calls that have not returned yet.", &[], "i64"),
+    command("get_queue_stats", "Get the per-second history of write queue depth and dropped writes.", &[], "Vec<QueueSnapshot>"),
+    command("reset_queue_stats", "Clear the write queue depth history.", &[], "()"),
+    command("set_queue_stats_capacity", "Set how many snapshots the write queue depth history keeps.", &[param("capacity", "usize")], "String"),
+    command("write_data_fire_and_forget", "Queue a write for the background writer and return immediately, dropped if the writer is behind.", &[param("charUuid", "Uuid"), param("service", "Uuid"), param("data", "Vec<u8>")], "()"),
+    command("list_known_devices", "List devices from the registry.", &[], "Vec<KnownDevice>"),
+    command("add_known_device", "Add or rename a device in the registry.", &[param("address", "String"), optional("alias", "String")], "KnownDevice"),
+    command("disconnect_and_forget", "Disconnect and remove a device with its saved data.", &[optional("address", "String")], "()"),
+    command("get_last_error", "Get the last reported error, if any.", &[], "Option<String>"),
+    command("list_active_tasks", "List the background tasks spawned since startup and whether they still run.", &[], "Vec<TaskInfo>"),
+    command("cancel_task", "Abort a background task by name, internal tasks such as frame_refill are refused.", &[param("name", "String")], "String"),
+    command("set_verbose_events", "Emit ble://operation-log for every connect, disconnect, write and read.", &[param("enabled", "bool")], "String"),
+    command("clear_last_error", "Clear the last reported error.", &[], "()"),
+    command("run_self_test", "Check BLE adapter, UUIDs, app state, write queue, rate limiter and saved profiles, emits ble://self-test-complete.", &[], "SelfTestReport"),
+    command("list_commands", "List all commands with their parameters.", &[], "Vec<CommandInfo>"),
+    command("describe_command", "Get the description of one command by name.", &[param("name", "String")], "Option<CommandInfo>"),
+];
+
+#[tauri::command]
+pub async fn list_commands() -> Result<Vec<CommandInfo>, String> {
+    Ok(COMMAND_REGISTRY.to_vec())
+}
+
+#[tauri::command]
+pub async fn describe_command(name: String) -> Result<Option<CommandInfo>, String> {
+    Ok(COMMAND_REGISTRY.iter().find(|command| command.name == name).copied())
+}
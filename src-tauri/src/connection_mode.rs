@@ -0,0 +1,134 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_blec::Handler;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::events::report;
+use crate::disconnect::{self, DisconnectReason};
+use crate::{lock, reconnect, AppState};
+
+pub const DEFAULT_DISCONNECT_AFTER_MS: u64 = 2000;
+// Longest idle delay before an on demand link is dropped
+pub const MAX_DISCONNECT_AFTER_MS: u64 = 600_000;
+
+/*
+    Persistent keeps the link up for the whole session.
+    OnDemand drops the link after `disconnect_after_ms` without writes and restores it before the next write,
+    the session (connected_address, is_connected) stays open in between. Subscriptions do not survive a dropped link.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionMode {
+    Persistent,
+    OnDemand,
+}
+
+impl ConnectionMode {
+    fn parse(value: &str) -> Result<Self, BleError> {
+        match value {
+            "persistent" => Ok(Self::Persistent),
+            "on_demand" => Ok(Self::OnDemand),
+            _ => Err(BleError::InvalidArgument(format!("Unsupported connection mode: {}", value))),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Persistent => "persistent",
+            Self::OnDemand => "on_demand",
+        }
+    }
+}
+
+pub(crate) fn cancel_idle_disconnect(state: &AppState) {
+    if let Some(task) = lock(&state.idle_disconnect_task).take() {
+        task.abort();
+    }
+}
+
+/*
+    Restore a link dropped by on demand mode before reading or writing. No-op in persistent mode.
+*/
+pub(crate) async fn ensure_link(state: &AppState, handler: &'static Handler) -> Result<(), BleError> {
+    if *lock(&state.connection_mode) != ConnectionMode::OnDemand {
+        return Ok(());
+    }
+    cancel_idle_disconnect(state);
+    if handler.is_connected() {
+        return Ok(());
+    }
+
+    let address = lock(&state.connected_address).clone().ok_or(BleError::NotConnected)?;
+    info!("On demand: connecting to {}", address);
+    handler
+        .connect(&address, reconnect::on_disconnect_handler(state), false)
+        .await
+        .map_err(|e| report(BleError::ConnectFailed { address, reason: e.to_string() }))
+}
+
+/*
+    Drop the link once no write followed within disconnect_after_ms. No-op in persistent mode.
+*/
+pub(crate) fn schedule_idle_disconnect(state: &AppState, handler: &'static Handler) {
+    if *lock(&state.connection_mode) != ConnectionMode::OnDemand {
+        return;
+    }
+    let delay = Duration::from_millis(*lock(&state.disconnect_after_ms));
+    let task = tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let app = crate::events::app_handle();
+        if let Some(app) = &app {
+            reconnect::expect_disconnect(&app.state::<AppState>(), true);
+        }
+        match handler.disconnect().await {
+            Ok(_) => info!("On demand: link idle, disconnected"),
+            Err(e) => {
+                info!("On demand: idle disconnect failed: {}", e);
+                if let Some(app) = &app {
+                    let state = app.state::<AppState>();
+                    reconnect::expect_disconnect(&state, false);
+                    disconnect::record_disconnect(&state, DisconnectReason::Error(format!("Idle disconnect failed: {}", e)));
+                }
+            }
+        }
+    });
+    crate::tasks::track(state, "idle_disconnect", &task);
+    if let Some(previous) = lock(&state.idle_disconnect_task).replace(task) {
+        previous.abort();
+    }
+}
+
+pub(crate) fn check_disconnect_after(delay_ms: u64) -> Result<(), BleError> {
+    if delay_ms == 0 || delay_ms > MAX_DISCONNECT_AFTER_MS {
+        return Err(BleError::InvalidArgument(format!("Disconnect delay must be between 1 and {} ms", MAX_DISCONNECT_AFTER_MS)));
+    }
+    Ok(())
+}
+
+/*
+    mode: "persistent" or "on_demand". `disconnect_after_ms` batches writes in on demand mode.
+    Switching back to persistent restores a dropped link.
+*/
+#[tauri::command]
+pub async fn set_connection_mode(state: tauri::State<'_, AppState>, mode: String, disconnect_after_ms: Option<u64>) -> Result<String, BleError> {
+    let mode = ConnectionMode::parse(&mode)?;
+    let disconnect_after_ms = disconnect_after_ms.unwrap_or(DEFAULT_DISCONNECT_AFTER_MS);
+    check_disconnect_after(disconnect_after_ms)?;
+    *lock(&state.connection_mode) = mode;
+    *lock(&state.disconnect_after_ms) = disconnect_after_ms;
+
+    if mode == ConnectionMode::Persistent {
+        cancel_idle_disconnect(&state);
+        if *lock(&state.is_connected) && !state.handler()?.is_connected() {
+            reconnect::reconnect_with_backoff(&state).await?;
+        }
+    }
+    Ok(format!("Connection mode set to: {} (disconnect after {} ms)", mode.as_str(), disconnect_after_ms))
+}
+
+#[tauri::command]
+pub async fn get_connection_mode(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    Ok(lock(&state.connection_mode).as_str().to_string())
+}
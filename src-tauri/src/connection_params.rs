@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::error::BleError;
+use crate::{ensure_connected, lock, AppState};
+
+// Limits of the Bluetooth Core specification
+const MIN_INTERVAL_MS: f32 = 7.5;
+const MAX_INTERVAL_MS: f32 = 4000.0;
+const MAX_LATENCY: u16 = 499;
+const MIN_SUPERVISION_TIMEOUT_MS: u32 = 100;
+const MAX_SUPERVISION_TIMEOUT_MS: u32 = 32000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ConnectionParams {
+    pub interval_min_ms: f32,
+    pub interval_max_ms: f32,
+    pub latency: u16,
+    pub supervision_timeout_ms: u32,
+}
+
+/*
+    Outcome of a parameter request. The central can only ask, the peripheral decides,
+    so `applied` only means the platform accepted the request.
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionParamsResult {
+    pub requested: ConnectionParams,
+    pub applied: bool,
+    pub detail: Option<String>,
+}
+
+impl ConnectionParams {
+    pub(crate) fn validate(&self) -> Result<(), BleError> {
+        let interval_range = MIN_INTERVAL_MS..=MAX_INTERVAL_MS;
+        if !interval_range.contains(&self.interval_min_ms) || !interval_range.contains(&self.interval_max_ms) {
+            return Err(BleError::InvalidArgument(format!("Connection interval must be within {}..={} ms", MIN_INTERVAL_MS, MAX_INTERVAL_MS)));
+        }
+        if self.interval_min_ms > self.interval_max_ms {
+            return Err(BleError::InvalidArgument("interval_min_ms must not exceed interval_max_ms".to_string()));
+        }
+        if self.latency > MAX_LATENCY {
+            return Err(BleError::InvalidArgument(format!("Slave latency must be at most {}", MAX_LATENCY)));
+        }
+        if !(MIN_SUPERVISION_TIMEOUT_MS..=MAX_SUPERVISION_TIMEOUT_MS).contains(&self.supervision_timeout_ms) {
+            return Err(BleError::InvalidArgument(format!("Supervision timeout must be within {}..={} ms", MIN_SUPERVISION_TIMEOUT_MS, MAX_SUPERVISION_TIMEOUT_MS)));
+        }
+        // Spec requires the timeout to outlast the longest possible gap between connection events
+        let min_timeout_ms = (1.0 + self.latency as f32) * self.interval_max_ms * 2.0;
+        if (self.supervision_timeout_ms as f32) <= min_timeout_ms {
+            return Err(BleError::InvalidArgument(format!("Supervision timeout must exceed {} ms for this interval and latency", min_timeout_ms)));
+        }
+        Ok(())
+    }
+}
+
+// tauri-plugin-blec does not expose BluetoothGatt::requestConnectionPriority or the parameter update request yet
+#[cfg(target_os = "android")]
+fn request_platform_parameters(_params: &ConnectionParams) -> Result<(), String> {
+    Err("Connection parameter requests are not exposed by the BLE plugin on Android".to_string())
+}
+
+// CoreBluetooth does not let a central request connection parameters, only the peripheral can
+#[cfg(target_os = "ios")]
+fn request_platform_parameters(_params: &ConnectionParams) -> Result<(), String> {
+    Err("iOS does not allow the central to request connection parameters".to_string())
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn request_platform_parameters(_params: &ConnectionParams) -> Result<(), String> {
+    Err("Connection parameter requests are not supported by the desktop backend".to_string())
+}
+
+fn request(params: ConnectionParams) -> ConnectionParamsResult {
+    let result = request_platform_parameters(&params);
+    if let Err(e) = &result {
+        info!("Connection parameters {:?} not applied: {}", params, e);
+    }
+    ConnectionParamsResult {
+        requested: params,
+        applied: result.is_ok(),
+        detail: result.err(),
+    }
+}
+
+/*
+    Ask the platform for a shorter connection interval to lower command latency.
+    The parameters are stored and requested again after every connect.
+*/
+#[tauri::command]
+pub async fn request_connection_parameters(
+    state: tauri::State<'_, AppState>,
+    interval_min_ms: f32,
+    interval_max_ms: f32,
+    latency: u16,
+    supervision_timeout_ms: u32,
+) -> Result<ConnectionParamsResult, BleError> {
+    ensure_connected(&state).await?;
+    let params = ConnectionParams { interval_min_ms, interval_max_ms, latency, supervision_timeout_ms };
+    params.validate()?;
+
+    *lock(&state.connection_params) = Some(params);
+    Ok(request(params))
+}
+
+/*
+    Request the stored parameters again, called by connect.
+*/
+pub(crate) fn reapply(state: &AppState) {
+    if let Some(params) = *lock(&state.connection_params) {
+        request(params);
+    }
+}
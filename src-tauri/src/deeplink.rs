@@ -0,0 +1,76 @@
+use serde::Serialize;
+use tauri::Manager;
+use tauri_plugin_deep_link::DeepLinkExt;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::{events, normalize_mac_address, AppState};
+
+const CONNECT_LINK_PREFIX: &str = "meguru://connect/";
+
+#[derive(Debug, Clone, Serialize)]
+struct DeepLinkConnect {
+    address: String,
+}
+
+/*
+    Address of a meguru://connect/{address} link, None for any other URL.
+*/
+fn parse_connect_link(url: &str) -> Option<Result<String, BleError>> {
+    let url = url.trim().to_lowercase();
+    let address = url.strip_prefix(CONNECT_LINK_PREFIX)?.trim_end_matches('/');
+    Some(normalize_mac_address(address))
+}
+
+/*
+    Connect to the device of a meguru://connect/{address} link in the background.
+    Emits ble://deep-link-connect before the connect attempt, other URLs are ignored.
+*/
+pub(crate) fn handle_url(app: &tauri::AppHandle, url: &str) {
+    let address = match parse_connect_link(url) {
+        None => return,
+        Some(Err(e)) => {
+            info!("Ignoring deep link {}: {}", url, e);
+            return;
+        }
+        Some(Ok(address)) => address,
+    };
+
+    info!("Deep link: connecting to {}", address);
+    events::emit("ble://deep-link-connect", DeepLinkConnect { address: address.clone() });
+    let task_app = app.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::connect(task_app.clone(), task_app.state::<AppState>(), &address).await {
+            info!("Deep link connect to {} failed: {}", address, e);
+        }
+    });
+    crate::tasks::track(&app.state::<AppState>(), "deep_link_connect", &handle);
+}
+
+/*
+    Route meguru:// links to handle_url, the link the app was launched with and links opened while it runs.
+    On desktop a second instance started by a link hands it over through the single instance plugin.
+*/
+pub(crate) fn init(app: &tauri::AppHandle) {
+    // ? Installers register the scheme, dev builds and AppImages have to register it at runtime
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        info!("Failed to register deep link schemes: {}", e);
+    }
+
+    match app.deep_link().get_current() {
+        Ok(urls) => {
+            for url in urls.unwrap_or_default() {
+                handle_url(app, url.as_str());
+            }
+        }
+        Err(e) => info!("Failed to get launch deep link: {}", e),
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            handle_url(&handle, url.as_str());
+        }
+    });
+}
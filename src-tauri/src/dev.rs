@@ -0,0 +1,121 @@
+use tracing::info;
+
+use crate::error::BleError;
+use crate::events::report;
+use crate::policy::{write_with_policy, WritePolicy};
+use crate::subscription::{self, parse_uuid, SubscriptionResult};
+use crate::{ensure_connected, ensure_writes_allowed, lock, parse_write_type, service_uuid, AppState, ATT_HEADER_SIZE};
+
+// Override at build time with MEGURU_DEV_PASSWORD=... cargo build
+const DEV_MODE_PASSWORD: &str = match option_env!("MEGURU_DEV_PASSWORD") {
+    Some(password) => password,
+    None => "meguru-dev",
+};
+
+#[tauri::command]
+pub async fn enable_dev_mode(state: tauri::State<'_, AppState>, password: String) -> Result<String, BleError> {
+    if password != DEV_MODE_PASSWORD {
+        return Err(BleError::InvalidArgument("Wrong dev mode password".to_string()));
+    }
+    *lock(&state.dev_mode) = true;
+    info!("Dev mode enabled");
+    Ok("Dev mode enabled.".to_string())
+}
+
+#[tauri::command]
+pub async fn disable_dev_mode(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    *lock(&state.dev_mode) = false;
+    Ok("Dev mode disabled.".to_string())
+}
+
+pub(crate) fn ensure_dev_mode(state: &AppState) -> Result<(), BleError> {
+    if *lock(&state.dev_mode) {
+        Ok(())
+    } else {
+        Err(report(BleError::DevModeRequired))
+    }
+}
+
+/*
+    Write arbitrary bytes to any characteristic without encoding.
+    Only available in dev mode, payload must fit into a single ATT packet.
+*/
+#[tauri::command]
+pub async fn send_raw_bytes(state: tauri::State<'_, AppState>, char_uuid: String, service_uuid: String, data: Vec<u8>, write_type: String) -> Result<(), BleError> {
+    ensure_dev_mode(&state)?;
+    ensure_connected(&state).await?;
+    ensure_writes_allowed(&state)?;
+
+    let char_uuid = parse_uuid(&char_uuid)?;
+    let service = parse_uuid(&service_uuid)?;
+    let write_type = parse_write_type(&write_type)?;
+
+    let max_len = lock(&state.negotiated_mtu).saturating_sub(ATT_HEADER_SIZE) as usize;
+    if data.len() > max_len {
+        return Err(BleError::InvalidArgument(format!("Payload of {} bytes exceeds {} bytes", data.len(), max_len)));
+    }
+
+    let handler = state.handler()?;
+
+    info!("send_raw_bytes: {:?} to {} (Service: {})", data, char_uuid, service);
+    let result = write_with_policy(handler, char_uuid, service, &data, &WritePolicy::once(write_type)).await;
+    lock(&state.session).record_write(result.is_ok());
+    result
+}
+
+/*
+    Subscribe to every characteristic of the service that supports notify or indicate, for firmware debugging.
+    Each one is emitted as ble://debug/{alias}, unknown characteristics use their UUID as alias.
+*/
+#[tauri::command]
+pub async fn subscribe_all_debug(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<Vec<SubscriptionResult>, BleError> {
+    ensure_dev_mode(&state)?;
+    ensure_connected(&state).await?;
+    let address = lock(&state.connected_address).clone().ok_or(BleError::NotConnected)?;
+
+    let handler = state.handler()?;
+    let service = service_uuid(&state);
+    let services = handler
+        .discover_services(&address)
+        .await
+        .map_err(|e| report(BleError::Command(format!("Discover services of {} failed: {}", address, e))))?;
+
+    let notifiable: Vec<_> = services
+        .iter()
+        .filter(|s| s.uuid == service)
+        .flat_map(|s| s.characteristics.iter())
+        .filter(|c| c.properties.notify || c.properties.indicate)
+        .map(|c| c.uuid)
+        .collect();
+    info!("subscribe_all_debug: {} notifiable characteristic(s)", notifiable.len());
+
+    let mut results = Vec::with_capacity(notifiable.len());
+    for char_uuid in notifiable {
+        let event_name = format!("ble://debug/{}", crate::alias::characteristic_label(char_uuid));
+        let outcome = subscription::subscribe(&app, &state, char_uuid, service, event_name).await;
+        if outcome.is_ok() {
+            lock(&state.debug_subscriptions).push(char_uuid);
+        }
+        results.push(SubscriptionResult {
+            char_uuid: char_uuid.to_string(),
+            success: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+        });
+    }
+    Ok(results)
+}
+
+#[tauri::command]
+pub async fn unsubscribe_all_debug(state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    ensure_dev_mode(&state)?;
+    let debug_subscriptions = std::mem::take(&mut *lock(&state.debug_subscriptions));
+
+    let handler = state.handler()?;
+    for char_uuid in debug_subscriptions {
+        if let Err(e) = handler.unsubscribe(char_uuid).await {
+            info!("Failed to unsubscribe {}: {}", char_uuid, e);
+        }
+        lock(&state.subscriptions).remove(&char_uuid);
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use serde::Serialize;
+use tracing::info;
+
+use crate::{lock, AppState};
+
+/*
+    Why the last connection ended, e.g. { "type": "user_initiated" } or { "type": "error", "message": "..." }
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "message", rename_all = "snake_case")]
+pub enum DisconnectReason {
+    UserInitiated,
+    // Writes stopped draining and the watchdog dropped the link
+    Timeout,
+    LinkLoss,
+    // A disconnect failed, the link state is unknown
+    Error(String),
+}
+
+pub(crate) fn record_disconnect(state: &AppState, reason: DisconnectReason) {
+    info!("Disconnect reason: {:?}", reason);
+    *lock(&state.last_disconnect_reason) = Some(reason);
+}
+
+/*
+    tauri-plugin-blec does not report why a link dropped, so a link closed by the device
+    cannot be told apart from other unexpected disconnects, both are recorded as LinkLoss.
+*/
+#[tauri::command]
+pub async fn get_last_disconnect_reason(state: tauri::State<'_, AppState>) -> Result<Option<DisconnectReason>, String> {
+    Ok(lock(&state.last_disconnect_reason).clone())
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+
+/*
+    Error type shared by the BLE commands.
+    Serialized as its display string so the frontend keeps receiving plain messages.
+*/
+#[derive(Debug, Clone)]
+pub enum BleError {
+    Command(String),
+    HandlerUnavailable(String),
+    NotConnected,
+    ProbeFailed(String),
+    InvalidUuid(String),
+    SubscribeFailed(String),
+    ReconnectFailed(String),
+    InvalidArgument(String),
+    WriteFailed(String),
+    WriteQueueFull(usize),
+    WritesPaused,
+    ReadFailed(String),
+    DevModeRequired,
+    ConnectFailed { address: String, reason: String },
+    ControllerNotUsable,
+    ScanTimeout(String),
+    ScanAlreadyInProgress,
+    InvalidPayload(String),
+    OutOfRange { value: u8, min: u8, max: u8 },
+    PreloadAlreadyRunning,
+    TransitionAlreadyRunning,
+    DeviceIdentityMismatch { address: String, expected_service: String },
+    Io(String),
+    InvalidScript(String),
+    ScriptAlreadyRunning,
+    SequenceCancelled,
+    InvalidProfileName(String),
+    AppShuttingDown,
+    ConfirmationTimeout { characteristic: String, timeout_ms: u64 },
+    FeatureIncompatible { feature: String, reason: String },
+    VerificationExhausted { characteristic: String, attempts: u8 },
+}
+
+impl fmt::Display for BleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BleError::Command(msg) => write!(f, "{}", msg),
+            BleError::HandlerUnavailable(msg) => write!(f, "Get handle failed: {}", msg),
+            BleError::NotConnected => write!(f, "Device is not connected"),
+            BleError::ProbeFailed(msg) => write!(f, "Probe failed: {}", msg),
+            BleError::InvalidUuid(msg) => write!(f, "Invalid UUID {}", msg),
+            BleError::SubscribeFailed(msg) => write!(f, "Subscription failed: {}", msg),
+            BleError::ReconnectFailed(addr) => write!(f, "Reconnect to {} failed", addr),
+            BleError::InvalidArgument(msg) => write!(f, "Invalid argument: {}", msg),
+            BleError::WriteFailed(msg) => write!(f, "Write failed: {}", msg),
+            BleError::WritesPaused => write!(f, "Writes are paused"),
+            BleError::WriteQueueFull(capacity) => write!(f, "Write queue is full ({} pending writes)", capacity),
+            BleError::ReadFailed(msg) => write!(f, "Read failed: {}", msg),
+            BleError::DevModeRequired => write!(f, "Dev mode is required for this command"),
+            BleError::ConnectFailed { address, reason } => write!(f, "Connect {:?} error occurred: {}", address, reason),
+            BleError::ControllerNotUsable => write!(f, "Controller is not usable, cannot send joystick data"),
+            BleError::ScanTimeout(addr) => write!(f, "Device {} not found within scan period", addr),
+            BleError::ScanAlreadyInProgress => write!(f, "A scan is already in progress"),
+            BleError::InvalidPayload(msg) => write!(f, "Invalid payload: {}", msg),
+            BleError::OutOfRange { value, min, max } => write!(f, "Value {} is outside the allowed range {}..={}", value, min, max),
+            BleError::PreloadAlreadyRunning => write!(f, "preload_operation is already running"),
+            BleError::TransitionAlreadyRunning => write!(f, "A lifting arm transition is already running"),
+            BleError::DeviceIdentityMismatch { address, expected_service } => write!(f, "Device {} does not expose service {}", address, expected_service),
+            BleError::Io(msg) => write!(f, "IO error: {}", msg),
+            BleError::InvalidScript(msg) => write!(f, "Invalid script: {}", msg),
+            BleError::ScriptAlreadyRunning => write!(f, "Another script is already running"),
+            BleError::SequenceCancelled => write!(f, "Arm sequence was cancelled"),
+            BleError::InvalidProfileName(name) => write!(f, "Invalid profile name: {:?}", name),
+            BleError::AppShuttingDown => write!(f, "The app is shutting down"),
+            BleError::ConfirmationTimeout { characteristic, timeout_ms } => write!(f, "{} did not confirm the command within {} ms", characteristic, timeout_ms),
+            BleError::FeatureIncompatible { feature, reason } => write!(f, "{} is not supported by the device firmware: {}", feature, reason),
+            BleError::VerificationExhausted { characteristic, attempts } => write!(f, "{} did not hold the written value after {} attempts", characteristic, attempts),
+        }
+    }
+}
+
+impl BleError {
+    /*
+        Actionable advice shown to the user alongside the error message.
+    */
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            BleError::HandlerUnavailable(_) => Some("Make sure Bluetooth is enabled on this device."),
+            BleError::ConnectFailed { .. } => Some("Check that the device address is correct and the device is powered on."),
+            BleError::ControllerNotUsable => Some("Wait for the device to finish initialization, then try again."),
+            BleError::ScanTimeout(_) => Some("Move closer to the device and try again."),
+            BleError::ScanAlreadyInProgress => Some("Wait for the running scan or connection attempt to finish."),
+            BleError::WritesPaused => Some("Resume writes when the maintenance is done."),
+            BleError::WriteQueueFull(_) => Some("Send input less often or wait for ble://queue-pressure-normal."),
+            BleError::DeviceIdentityMismatch { .. } => Some("Make sure the address belongs to a Meguru controller."),
+            BleError::ConfirmationTimeout { .. } => Some("Check that the arm can move freely, then try again."),
+            BleError::FeatureIncompatible { .. } => Some("Update the device firmware to use this feature."),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for BleError {}
+
+impl serde::Serialize for BleError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+// Commands that still report plain strings can be used with `?`
+impl From<String> for BleError {
+    fn from(msg: String) -> Self {
+        BleError::Command(msg)
+    }
+}
+
+impl From<BleError> for String {
+    fn from(e: BleError) -> Self {
+        e.to_string()
+    }
+}
+
+impl From<std::io::Error> for BleError {
+    fn from(e: std::io::Error) -> Self {
+        BleError::Io(e.to_string())
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::OnceLock;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+use uuid::Uuid;
+
+use crate::error::BleError;
+use crate::{lock, AppState};
+
+static APP_HANDLE: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+/*
+    Keep the app handle so helpers without access to a command context can emit events.
+    Called once from setup().
+*/
+pub(crate) fn init(handle: tauri::AppHandle) {
+    let _ = APP_HANDLE.set(handle);
+}
+
+pub(crate) fn app_handle() -> Option<tauri::AppHandle> {
+    APP_HANDLE.get().cloned()
+}
+
+pub(crate) fn emit<S: Serialize + Clone>(event: &str, payload: S) {
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle.emit(event, payload);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct OperationLog {
+    op: String,
+    char_uuid: Option<String>,
+    data: Option<Vec<u8>>,
+    success: bool,
+    duration_us: u64,
+}
+
+pub(crate) fn verbose(state: &AppState) -> bool {
+    *lock(&state.verbose_events)
+}
+
+/*
+    Emit ble://operation-log for a finished connect, disconnect, write or read when verbose events are enabled.
+*/
+pub(crate) fn log_operation(state: &AppState, op: &str, char_uuid: Option<Uuid>, data: Option<&[u8]>, success: bool, started: Instant) {
+    if !verbose(state) {
+        return;
+    }
+    emit("ble://operation-log", OperationLog {
+        op: op.to_string(),
+        char_uuid: char_uuid.map(|uuid| uuid.to_string()),
+        data: data.map(<[u8]>::to_vec),
+        success,
+        duration_us: started.elapsed().as_micros() as u64,
+    });
+}
+
+/*
+    Emit ble://operation-log for every BLE operation, too noisy for production use.
+*/
+#[tauri::command]
+pub async fn set_verbose_events(state: tauri::State<'_, AppState>, enabled: bool) -> Result<String, String> {
+    *lock(&state.verbose_events) = enabled;
+    Ok(format!("Verbose events set to: {}", enabled))
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ErrorEvent {
+    message: String,
+    suggestion: Option<String>,
+}
+
+/*
+    Record `err` as the last error, emit ble://error with its recovery suggestion
+    and hand the error back to the caller.
+*/
+pub(crate) fn report(err: BleError) -> BleError {
+    if let Some(handle) = APP_HANDLE.get() {
+        let state = handle.state::<AppState>();
+        *lock(&state.last_error) = Some(err.clone());
+        state.error_count.fetch_add(1, Ordering::SeqCst);
+    }
+    emit("ble://error", ErrorEvent {
+        message: err.to_string(),
+        suggestion: err.suggestion().map(str::to_string),
+    });
+    err
+}
+
+#[tauri::command]
+pub async fn get_last_error(state: tauri::State<'_, AppState>) -> Result<Option<BleError>, String> {
+    Ok(lock(&state.last_error).clone())
+}
+
+#[tauri::command]
+pub async fn clear_last_error(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    *lock(&state.last_error) = None;
+    Ok(())
+}
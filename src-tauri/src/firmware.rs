@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::fmt;
+use serde::Serialize;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::error::BleError;
+use crate::{events, lock, AppState};
+
+// Standard Device Information Service (0x180A) and Firmware Revision String characteristic (0x2A26)
+const DEVICE_INFORMATION_SERVICE_UUID: Uuid = Uuid::from_bytes([0x00, 0x00, 0x18, 0x0A, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0x80, 0x5F, 0x9B, 0x34, 0xFB]);
+const FIRMWARE_REVISION_CHARACTERISTIC_UUID: Uuid = Uuid::from_bytes([0x00, 0x00, 0x2A, 0x26, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0x80, 0x5F, 0x9B, 0x34, 0xFB]);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct FirmwareVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl FirmwareVersion {
+    const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /*
+        Parse "1.2.3", "v1.2" or "1.2.3-beta", missing parts are 0 and suffixes are ignored.
+    */
+    fn parse(value: &str) -> Result<Self, BleError> {
+        let invalid = || BleError::InvalidPayload(format!("Unsupported firmware version: {:?}", value));
+        let core = value.trim().trim_start_matches(['v', 'V']);
+        let core = core.split(['-', '+', ' ']).next().unwrap_or_default();
+        let mut parts = core.split('.').map(|part| part.parse::<u32>().map_err(|_| invalid()));
+        let major = parts.next().ok_or_else(invalid)??;
+        let minor = parts.next().transpose()?.unwrap_or(0);
+        let patch = parts.next().transpose()?.unwrap_or(0);
+        Ok(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/*
+    Firmware versions supporting a feature, `min` inclusive and `max` exclusive.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct SemanticVersionRange {
+    pub min: FirmwareVersion,
+    pub max: Option<FirmwareVersion>,
+}
+
+impl SemanticVersionRange {
+    fn contains(&self, version: FirmwareVersion) -> bool {
+        version >= self.min && self.max.is_none_or(|max| version < max)
+    }
+}
+
+impl fmt::Display for SemanticVersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) => write!(f, ">={}, <{}", self.min, max),
+            None => write!(f, ">={}", self.min),
+        }
+    }
+}
+
+pub type FeatureCompatibilityTable = HashMap<String, SemanticVersionRange>;
+
+/*
+    Features that need a minimum firmware, checked on every connect.
+*/
+fn feature_compatibility_table() -> FeatureCompatibilityTable {
+    [
+        ("heartbeat", SemanticVersionRange { min: FirmwareVersion::new(1, 1, 0), max: None }),
+        ("batch_write", SemanticVersionRange { min: FirmwareVersion::new(1, 2, 0), max: None }),
+    ]
+    .into_iter()
+    .map(|(feature, range)| (feature.to_string(), range))
+    .collect()
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompatibilityReport {
+    pub compatible_features: Vec<String>,
+    // Feature name and the reason it is not supported
+    pub incompatible_features: Vec<(String, String)>,
+}
+
+impl CompatibilityReport {
+    fn new(version: FirmwareVersion) -> Self {
+        let mut report = Self::default();
+        for (feature, range) in feature_compatibility_table() {
+            if range.contains(version) {
+                report.compatible_features.push(feature);
+            } else {
+                let reason = format!("Requires firmware {}, device has {}", range, version);
+                report.incompatible_features.push((feature, reason));
+            }
+        }
+        report.compatible_features.sort();
+        report.incompatible_features.sort();
+        report
+    }
+}
+
+/*
+    Reject a feature the last compatibility check found unsupported.
+    Features are allowed when the firmware version could not be read.
+*/
+pub(crate) fn ensure_feature_compatible(state: &AppState, feature: &str) -> Result<(), BleError> {
+    let report = lock(&state.feature_compatibility);
+    match report.as_ref().and_then(|report| report.incompatible_features.iter().find(|(name, _)| name == feature)) {
+        Some((_, reason)) => Err(BleError::FeatureIncompatible { feature: feature.to_string(), reason: reason.clone() }),
+        None => Ok(()),
+    }
+}
+
+/*
+    Read the firmware revision from the standard Device Information Service.
+*/
+#[tauri::command]
+pub async fn get_firmware_version(state: tauri::State<'_, AppState>) -> Result<FirmwareVersion, BleError> {
+    let data = crate::receive_data(state, FIRMWARE_REVISION_CHARACTERISTIC_UUID, DEVICE_INFORMATION_SERVICE_UUID).await?;
+    FirmwareVersion::parse(&String::from_utf8_lossy(&data))
+}
+
+/*
+    Check the features of the compatibility table against the firmware of the connected device.
+    Called on every connect, the report is emitted as ble://compatibility-report and gates the listed features.
+*/
+#[tauri::command]
+pub async fn check_feature_compatibility(state: tauri::State<'_, AppState>) -> Result<CompatibilityReport, BleError> {
+    // A failed check must not keep the report of the previous device
+    *lock(&state.feature_compatibility) = None;
+    let version = get_firmware_version(state.clone()).await?;
+    let report = CompatibilityReport::new(version);
+    info!("Firmware {} compatibility: {:?}", version, report);
+    *lock(&state.feature_compatibility) = Some(report.clone());
+    events::emit("ble://compatibility-report", report.clone());
+    Ok(report)
+}
@@ -0,0 +1,65 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::Manager;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::error::BleError;
+use crate::policy::WritePolicy;
+use crate::subscription::parse_uuid;
+use crate::{ensure_connected, lock, service_uuid, AppState};
+
+// Default heartbeat characteristic, override with set_heartbeat_characteristic for other firmware
+pub const HEARTBEAT_CHARACTERISTIC_UUID: Uuid = Uuid::from_bytes([0x00, 0x81, 0x19, 0x14, 0x45, 0x11, 0x19, 0x19, 0x19, 0x19, 0x45, 0x11, 0x00, 0x00, 0x00, 0xBE]);
+const HEARTBEAT_VALUE: u8 = 0x00;
+
+/*
+    Write HEARTBEAT_VALUE to the heartbeat characteristic every `interval_ms`, so devices that sleep
+    without traffic stay awake. A beat is skipped while other writes are queued, it has the lowest priority.
+*/
+#[tauri::command]
+pub async fn start_heartbeat(app: tauri::AppHandle, state: tauri::State<'_, AppState>, interval_ms: u64) -> Result<String, String> {
+    if interval_ms == 0 {
+        return Err("Interval must be greater than 0".to_string());
+    }
+    ensure_connected(&state).await?;
+    crate::firmware::ensure_feature_compatible(&state, "heartbeat")?;
+
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+        loop {
+            interval.tick().await;
+            let state = app.state::<AppState>();
+            let idle = state.write_queue_depth.load(Ordering::SeqCst) == 0;
+            if !*lock(&state.is_connected) || *lock(&state.writes_paused) || !idle {
+                continue;
+            }
+            let char_uuid = *lock(&state.heartbeat_characteristic);
+            let service = service_uuid(&state);
+            if let Err(e) = crate::write_data_as(state, char_uuid, service, vec![HEARTBEAT_VALUE], WritePolicy::best_effort()).await {
+                info!("Heartbeat write failed: {}", e);
+            }
+        }
+    });
+
+    crate::tasks::track(&state, "heartbeat", &handle);
+    if let Some(previous) = lock(&state.heartbeat_task).replace(handle) {
+        previous.abort();
+    }
+    Ok(format!("Heartbeat started with {} ms interval", interval_ms))
+}
+
+#[tauri::command]
+pub async fn stop_heartbeat(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    if let Some(handle) = lock(&state.heartbeat_task).take() {
+        handle.abort();
+    }
+    Ok("Heartbeat stopped.".to_string())
+}
+
+#[tauri::command]
+pub async fn set_heartbeat_characteristic(state: tauri::State<'_, AppState>, uuid: String) -> Result<String, BleError> {
+    let uuid = parse_uuid(&uuid)?;
+    *lock(&state.heartbeat_characteristic) = uuid;
+    Ok(format!("Heartbeat characteristic set to: {}", uuid))
+}
@@ -0,0 +1,568 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::{lock, AppState, JOYSTICK_ZERO_VALUE};
+
+pub const DEFAULT_RELATIVE_STEP_SIZE: u8 = 8;
+const JOYSTICK_CURVE_POWER_RANGE: std::ops::RangeInclusive<f32> = 0.1..=4.0;
+
+/*
+    Response curve of normalized joystick input, power 1.0 is linear, 2.0 quadratic, 0.5 square root.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JoystickCurve {
+    pub power: f32,
+}
+
+impl Default for JoystickCurve {
+    fn default() -> Self {
+        Self { power: 1.0 }
+    }
+}
+
+impl JoystickCurve {
+    fn apply(self, value: f32) -> f32 {
+        let value = value.clamp(-1.0, 1.0);
+        value.signum() * value.abs().powf(self.power)
+    }
+}
+
+/*
+    Shape of the X/Y dead region. Circular centers both axes when the vector magnitude is inside the deadzone,
+    Square centers each axis on its own when its offset is inside the deadzone.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadbandShape {
+    Square,
+    Circular,
+}
+
+impl DeadbandShape {
+    fn parse(value: &str) -> Result<Self, BleError> {
+        match value {
+            "square" => Ok(Self::Square),
+            "circular" => Ok(Self::Circular),
+            _ => Err(BleError::InvalidArgument(format!("Unsupported deadband shape: {}", value))),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Square => "square",
+            Self::Circular => "circular",
+        }
+    }
+}
+
+/*
+    How the R characteristic interprets its byte.
+    Joystick: 0x7F is center like X/Y. Angle: 0x00..0xFF is 0°..360°. Signed: 0x00 = -127, 0x7F = 0, 0xFF = +127.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RAxisSemantics {
+    Joystick,
+    Angle,
+    Signed,
+}
+
+impl RAxisSemantics {
+    fn parse(value: &str) -> Result<Self, BleError> {
+        match value {
+            "joystick" => Ok(Self::Joystick),
+            "angle" => Ok(Self::Angle),
+            "signed" => Ok(Self::Signed),
+            _ => Err(BleError::InvalidArgument(format!("Unsupported R axis semantics: {}", value))),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Joystick => "joystick",
+            Self::Angle => "angle",
+            Self::Signed => "signed",
+        }
+    }
+}
+
+/*
+    Map a normalized axis value (-1.0..=1.0) to a joystick byte, 0.0 maps to 0x7F.
+*/
+pub(crate) fn normalized_to_byte(value: f32) -> u8 {
+    let value = value.clamp(-1.0, 1.0);
+    let zero = JOYSTICK_ZERO_VALUE as f32;
+    let byte = if value >= 0.0 {
+        zero + value * (u8::MAX as f32 - zero)
+    } else {
+        zero + value * zero
+    };
+    byte.round() as u8
+}
+
+/*
+    Map a gamepad axis (i16::MIN..=i16::MAX) to a joystick byte, 0 maps to 0x7F.
+    Integer arithmetic with rounding, each half of the range is scaled separately so both ends are reached.
+*/
+pub(crate) fn i16_to_byte(value: i16) -> u8 {
+    let zero = JOYSTICK_ZERO_VALUE as i32;
+    let value = value as i32;
+    let byte = if value >= 0 {
+        let span = i16::MAX as i32;
+        zero + (value * (u8::MAX as i32 - zero) + span / 2) / span
+    } else {
+        let span = -(i16::MIN as i32);
+        zero - (-value * zero + span / 2) / span
+    };
+    byte as u8
+}
+
+/*
+    Map the R input according to the configured semantics.
+    Joystick: -1.0..=1.0. Angle: degrees, wrapped to 0..360. Signed: -127.0..=127.0.
+*/
+pub(crate) fn r_to_byte(value: f32, semantics: RAxisSemantics) -> u8 {
+    match semantics {
+        RAxisSemantics::Joystick => normalized_to_byte(value),
+        RAxisSemantics::Angle => (value.rem_euclid(360.0) / 360.0 * 256.0).floor().min(u8::MAX as f32) as u8,
+        RAxisSemantics::Signed => (JOYSTICK_ZERO_VALUE as f32 + value.clamp(-127.0, 127.0)).round().clamp(0.0, u8::MAX as f32) as u8,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct JoystickPolar {
+    pub angle_degrees: f32,
+    pub magnitude: f32,
+}
+
+/*
+    Convert joystick bytes to polar coordinates around the 0x7F center.
+    angle_degrees is in 0..360, magnitude is normalized to 0.0..=1.0.
+*/
+pub fn joystick_polar(x: u8, y: u8) -> JoystickPolar {
+    let x_centered = x as f32 - JOYSTICK_ZERO_VALUE as f32;
+    let y_centered = y as f32 - JOYSTICK_ZERO_VALUE as f32;
+
+    let mut angle_degrees = y_centered.atan2(x_centered).to_degrees();
+    if angle_degrees < 0.0 {
+        angle_degrees += 360.0;
+    }
+    let magnitude = (x_centered.hypot(y_centered) / JOYSTICK_ZERO_VALUE as f32).min(1.0);
+
+    JoystickPolar { angle_degrees, magnitude }
+}
+
+// Center a single axis when its offset from 0x7F is inside the deadzone
+fn axis_deadband(value: u8, deadzone: f32) -> u8 {
+    let offset = (value as f32 - JOYSTICK_ZERO_VALUE as f32).abs() / JOYSTICK_ZERO_VALUE as f32;
+    if offset < deadzone {
+        JOYSTICK_ZERO_VALUE
+    } else {
+        value
+    }
+}
+
+/*
+    Center X and Y inside the deadzone according to the deadband shape.
+    R has its own square deadband, only when it is a joystick axis.
+*/
+pub(crate) fn apply_deadzone(state: &AppState, x: u8, y: u8, r: u8) -> (u8, u8, u8) {
+    let deadzone = *lock(&state.joystick_deadzone);
+    let (x, y) = match *lock(&state.deadband_shape) {
+        DeadbandShape::Circular if joystick_polar(x, y).magnitude < deadzone => (JOYSTICK_ZERO_VALUE, JOYSTICK_ZERO_VALUE),
+        DeadbandShape::Circular => (x, y),
+        DeadbandShape::Square => (axis_deadband(x, deadzone), axis_deadband(y, deadzone)),
+    };
+    let r = match *lock(&state.r_axis_semantics) {
+        RAxisSemantics::Joystick => axis_deadband(r, deadzone),
+        _ => r,
+    };
+    (x, y, r)
+}
+
+#[tauri::command]
+pub async fn calculate_joystick_polar(x: u8, y: u8) -> Result<JoystickPolar, String> {
+    Ok(joystick_polar(x, y))
+}
+
+/*
+    Set deadzone as a fraction of the full joystick range (0.0 disables it), see set_deadband_shape.
+*/
+#[tauri::command]
+pub async fn set_joystick_deadzone(state: tauri::State<'_, AppState>, deadzone: f32) -> Result<String, String> {
+    if !(0.0..=1.0).contains(&deadzone) {
+        return Err(format!("Deadzone must be between 0.0 and 1.0, got {}", deadzone));
+    }
+    *lock(&state.joystick_deadzone) = deadzone;
+    Ok(format!("Joystick deadzone set to: {}", deadzone))
+}
+
+/*
+    Set the X/Y deadband shape, "circular" (default) or "square".
+*/
+#[tauri::command]
+pub async fn set_deadband_shape(state: tauri::State<'_, AppState>, shape: String) -> Result<String, BleError> {
+    let parsed = DeadbandShape::parse(&shape)?;
+    *lock(&state.deadband_shape) = parsed;
+    Ok(format!("Deadband shape set to: {}", parsed.as_str()))
+}
+
+#[tauri::command]
+pub async fn get_deadband_shape(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    Ok(lock(&state.deadband_shape).as_str().to_string())
+}
+
+/*
+    Set the response curve power of send_joystick_normalized (0.1 to 4.0), applied to every axis alike.
+*/
+#[tauri::command]
+pub async fn set_joystick_curve(state: tauri::State<'_, AppState>, power: f32) -> Result<String, String> {
+    if !JOYSTICK_CURVE_POWER_RANGE.contains(&power) {
+        return Err(format!("Curve power must be between 0.1 and 4.0, got {}", power));
+    }
+    lock(&state.joystick_curve).power = power;
+    Ok(format!("Joystick curve power set to: {}", power))
+}
+
+#[tauri::command]
+pub async fn get_joystick_curve(state: tauri::State<'_, AppState>) -> Result<f32, String> {
+    Ok(lock(&state.joystick_curve).power)
+}
+
+#[tauri::command]
+pub async fn set_r_axis_semantics(state: tauri::State<'_, AppState>, semantics: String) -> Result<String, BleError> {
+    let parsed = RAxisSemantics::parse(&semantics)?;
+    *lock(&state.r_axis_semantics) = parsed;
+    Ok(format!("R axis semantics set to: {}", parsed.as_str()))
+}
+
+#[tauri::command]
+pub async fn get_r_axis_semantics(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    Ok(lock(&state.r_axis_semantics).as_str().to_string())
+}
+
+// Reject NaN and infinite axis values, `name` is the axis reported in the error
+fn ensure_finite(name: &str, value: f32) -> Result<(), BleError> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(BleError::InvalidArgument(format!("{} is NaN or Inf", name)))
+    }
+}
+
+/*
+    Send joystick data from normalized floats.
+    x and y are in -1.0..=1.0 with 0.0 at center, r follows the configured R axis semantics.
+    The joystick curve is applied to X, Y and joystick-style R before conversion.
+    In low battery mode the X/Y magnitude is limited to half range. NaN and infinite inputs are rejected.
+*/
+#[tauri::command]
+pub async fn send_joystick_normalized(state: tauri::State<'_, AppState>, x: f32, y: f32, r: f32) -> Result<String, String> {
+    ensure_finite("x", x)?;
+    ensure_finite("y", y)?;
+    ensure_finite("r", r)?;
+    let semantics = *lock(&state.r_axis_semantics);
+    let curve = *lock(&state.joystick_curve);
+    let (x, y) = crate::battery::limit_for_battery(&state, curve.apply(x), curve.apply(y));
+    let x = normalized_to_byte(x);
+    let y = normalized_to_byte(y);
+    let r = match semantics {
+        RAxisSemantics::Joystick => r_to_byte(curve.apply(r), semantics),
+        _ => r_to_byte(r, semantics),
+    };
+    crate::send_joystick_data(state, x, y, r).await
+}
+
+/*
+    Send joystick data from gamepad axes reported as i16, 0 is center.
+    R is mapped as a joystick axis whatever the R axis semantics.
+*/
+#[tauri::command]
+pub async fn send_joystick_i16(state: tauri::State<'_, AppState>, x: i16, y: i16, r: i16) -> Result<String, BleError> {
+    Ok(crate::send_joystick_data(state, i16_to_byte(x), i16_to_byte(y), i16_to_byte(r)).await?)
+}
+
+fn offset(value: u8, delta: i8) -> u8 {
+    value.saturating_add_signed(delta)
+}
+
+/*
+    Move X/Y/R by signed deltas relative to the last sent arm state, saturating at 0x00/0xFF.
+*/
+#[tauri::command]
+pub async fn move_relative(state: tauri::State<'_, AppState>, dx: i8, dy: i8, dr: i8) -> Result<(), BleError> {
+    let (x, y, r) = {
+        let last = lock(&state.last_arm_state);
+        (offset(last.x, dx), offset(last.y, dy), offset(last.r, dr))
+    };
+    crate::send_joystick_data(state, x, y, r).await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_relative_step_size(state: tauri::State<'_, AppState>) -> Result<u8, String> {
+    Ok(*lock(&state.relative_step_size))
+}
+
+#[tauri::command]
+pub async fn set_relative_step_size(state: tauri::State<'_, AppState>, step: u8) -> Result<String, String> {
+    *lock(&state.relative_step_size) = step;
+    Ok(format!("Relative step size set to: {}", step))
+}
+
+fn step(state: &AppState) -> i8 {
+    i8::try_from(*lock(&state.relative_step_size)).unwrap_or(i8::MAX)
+}
+
+#[tauri::command]
+pub async fn move_left(state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    let step = step(&state);
+    move_relative(state, -step, 0, 0).await
+}
+
+#[tauri::command]
+pub async fn move_right(state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    let step = step(&state);
+    move_relative(state, step, 0, 0).await
+}
+
+#[tauri::command]
+pub async fn move_up(state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    let step = step(&state);
+    move_relative(state, 0, step, 0).await
+}
+
+#[tauri::command]
+pub async fn move_down(state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    let step = step(&state);
+    move_relative(state, 0, -step, 0).await
+}
+
+// Largest offset from center that fits on both sides of 0x7F
+const MAX_DIRECTION_SPEED: u8 = 127;
+
+/*
+    Absolute movement for keyboard style input, `speed` (0..=127) is the offset from center.
+    direction: "forward", "backward", "left", "right", "rotate_cw", "rotate_ccw" or "stop".
+*/
+#[tauri::command]
+pub async fn move_in_direction(state: tauri::State<'_, AppState>, direction: String, speed: u8) -> Result<(), BleError> {
+    if speed > MAX_DIRECTION_SPEED {
+        return Err(BleError::OutOfRange { value: speed, min: 0, max: MAX_DIRECTION_SPEED });
+    }
+    let center = JOYSTICK_ZERO_VALUE;
+    let high = center + speed;
+    let low = center - speed;
+
+    let (x, y, r) = match direction.as_str() {
+        "forward" => (center, high, center),
+        "backward" => (center, low, center),
+        "left" => (low, center, center),
+        "right" => (high, center, center),
+        "rotate_cw" => (center, center, high),
+        "rotate_ccw" => (center, center, low),
+        "stop" => (center, center, center),
+        _ => return Err(BleError::InvalidArgument(format!("Unsupported direction: {}", direction))),
+    };
+    crate::send_joystick_data(state, x, y, r).await?;
+    Ok(())
+}
+
+/*
+    Joystick position the background poll task keeps sending to the device.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoystickInput {
+    pub x: u8,
+    pub y: u8,
+    pub r: u8,
+}
+
+impl Default for JoystickInput {
+    fn default() -> Self {
+        Self {
+            x: JOYSTICK_ZERO_VALUE,
+            y: JOYSTICK_ZERO_VALUE,
+            r: JOYSTICK_ZERO_VALUE,
+        }
+    }
+}
+
+/*
+    Newest joystick frame waiting to be written and whether a caller is currently writing frames.
+*/
+#[derive(Debug, Default)]
+pub struct JoystickCoalescer {
+    pending: Option<JoystickInput>,
+    flushing: bool,
+}
+
+/*
+    Clears JoystickCoalescer::flushing if the flushing caller is dropped before it finished.
+*/
+struct FlushGuard<'a> {
+    state: &'a AppState,
+    armed: bool,
+}
+
+impl Drop for FlushGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            lock(&self.state.joystick_coalescer).flushing = false;
+        }
+    }
+}
+
+/*
+    Queue `frame` as the newest joystick frame. If no caller is writing frames this one becomes the flusher:
+    it yields once per tick and writes the newest pending frame until none is left, so at most one
+    joystick write is in flight and frames replaced in the meantime are never sent.
+*/
+pub(crate) async fn coalesce_frame(state: tauri::State<'_, AppState>, frame: JoystickInput) -> Result<String, String> {
+    {
+        let mut coalescer = lock(&state.joystick_coalescer);
+        coalescer.pending = Some(frame);
+        if coalescer.flushing {
+            return Ok(format!("Joystick frame coalesced: X={}, Y={}, R={}", frame.x, frame.y, frame.r));
+        }
+        coalescer.flushing = true;
+    }
+
+    let mut guard = FlushGuard { state: &state, armed: true };
+    let mut result = Ok(String::new());
+    loop {
+        tokio::task::yield_now().await;
+        let next = {
+            let mut coalescer = lock(&state.joystick_coalescer);
+            let next = coalescer.pending.take();
+            // ! Cleared under the same lock new frames are queued with, so no frame is left behind
+            if next.is_none() {
+                coalescer.flushing = false;
+                guard.armed = false;
+            }
+            next
+        };
+        let Some(frame) = next else {
+            return result;
+        };
+        result = crate::write_joystick_frame(state.clone(), frame.x, frame.y, frame.r).await;
+    }
+}
+
+#[tauri::command]
+pub async fn set_coalesce_joystick_writes(state: tauri::State<'_, AppState>, enabled: bool) -> Result<String, String> {
+    *lock(&state.coalesce_joystick_writes) = enabled;
+    Ok(format!("Joystick write coalescing: {}", enabled))
+}
+
+#[tauri::command]
+pub async fn set_joystick_target(state: tauri::State<'_, AppState>, x: u8, y: u8, r: u8) -> Result<(), String> {
+    *lock(&state.joystick_target) = JoystickInput { x, y, r };
+    Ok(())
+}
+
+/*
+    Send the joystick target every `interval_ms` while connected,
+    so the frontend only updates the target and the backend owns the BLE timing.
+*/
+#[tauri::command]
+pub async fn start_joystick_poll(app: tauri::AppHandle, state: tauri::State<'_, AppState>, interval_ms: u64) -> Result<String, String> {
+    if interval_ms == 0 {
+        return Err("Interval must be greater than 0".to_string());
+    }
+
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+        loop {
+            interval.tick().await;
+            let state = app.state::<AppState>();
+            if !*lock(&state.is_connected) {
+                continue;
+            }
+            let target = *lock(&state.joystick_target);
+            if let Err(e) = crate::send_joystick_data(state, target.x, target.y, target.r).await {
+                info!("Joystick poll failed to send target: {}", e);
+            }
+        }
+    });
+
+    crate::tasks::track(&state, "joystick_poll", &handle);
+    if let Some(previous) = lock(&state.joystick_poll_task).replace(handle) {
+        previous.abort();
+    }
+    Ok(format!("Joystick poll started with {} ms interval", interval_ms))
+}
+
+#[tauri::command]
+pub async fn stop_joystick_poll(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    if let Some(handle) = lock(&state.joystick_poll_task).take() {
+        handle.abort();
+    }
+    Ok("Joystick poll stopped.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVE_POWERS: [f32; 5] = [0.1, 0.5, 1.0, 2.0, 4.0];
+
+    #[test]
+    fn curve_keeps_endpoints() {
+        for power in CURVE_POWERS {
+            let curve = JoystickCurve { power };
+            assert_eq!(curve.apply(-1.0), -1.0, "power {}", power);
+            assert_eq!(curve.apply(0.0), 0.0, "power {}", power);
+            assert_eq!(curve.apply(1.0), 1.0, "power {}", power);
+        }
+    }
+
+    #[test]
+    fn curve_is_monotonic_and_keeps_sign() {
+        for power in CURVE_POWERS {
+            let curve = JoystickCurve { power };
+            let mut previous = curve.apply(-1.0);
+            for step in -99..=100 {
+                let value = step as f32 / 100.0;
+                let curved = curve.apply(value);
+                assert!(curved >= previous, "power {}: apply({}) = {} < {}", power, value, curved, previous);
+                assert_eq!(curved.signum(), value.signum(), "power {}: apply({}) = {}", power, value, curved);
+                assert!((-1.0..=1.0).contains(&curved), "power {}: apply({}) = {}", power, value, curved);
+                previous = curved;
+            }
+        }
+    }
+
+    #[test]
+    fn ensure_finite_rejects_nan_and_inf() {
+        for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert!(matches!(ensure_finite("x", value), Err(BleError::InvalidArgument(_))), "{} accepted", value);
+        }
+    }
+
+    #[test]
+    fn ensure_finite_accepts_finite_values() {
+        for value in [-1.0, 0.0, 1.0, f32::MAX, f32::MIN] {
+            assert!(ensure_finite("x", value).is_ok(), "{} rejected", value);
+        }
+    }
+
+    #[test]
+    fn i16_to_byte_endpoints() {
+        assert_eq!(i16_to_byte(i16::MIN), 0x00);
+        assert_eq!(i16_to_byte(0), 0x7F);
+        assert_eq!(i16_to_byte(i16::MAX), 0xFF);
+    }
+
+    #[test]
+    fn i16_to_byte_is_monotonic() {
+        let mut previous = i16_to_byte(i16::MIN);
+        for value in (i16::MIN..=i16::MAX).skip(1) {
+            let byte = i16_to_byte(value);
+            assert!(byte >= previous, "i16_to_byte({}) = {} < {}", value, byte, previous);
+            previous = byte;
+        }
+    }
+}
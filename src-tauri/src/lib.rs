@@ -1,10 +1,54 @@
-use std::{sync::Mutex};
+#![deny(clippy::unwrap_used)]
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard, atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering}};
 use tokio::sync::mpsc;
-use tracing::info;
+use tracing::{info, info_span, Instrument};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use tauri_plugin_blec::{OnDisconnectHandler, models::{ScanFilter, WriteType, BleDevice}};
+use tauri_plugin_blec::{Handler, models::{ScanFilter, WriteType, BleDevice}};
 use tauri::Manager;
 
+mod adapter;
+mod alias;
+mod batch;
+mod battery;
+mod claw;
+mod commands;
+mod connection_mode;
+mod connection_params;
+mod deeplink;
+mod dev;
+mod disconnect;
+mod error;
+mod events;
+mod firmware;
+mod heartbeat;
+mod joystick;
+mod logs;
+mod metrics;
+mod policy;
+mod position;
+mod probe;
+mod profile;
+mod quality;
+mod queue;
+mod rate_limit;
+mod reconnect;
+mod registry;
+mod script;
+mod selftest;
+mod sequence;
+mod session;
+mod status;
+mod subscription;
+mod survey;
+mod tasks;
+mod workspace;
+
+use error::BleError;
+use events::report;
+
 /* 
 // Transfer Standard UUID defined by bluetooth SIG to 128bit UUID format
 const fn transfer_standard_u16_to_u128(value: u16) -> Uuid {
@@ -29,9 +73,27 @@ const LIFTING_ARM_CHARACTERISTIC_C_UUID: Uuid = Uuid::from_bytes([0x00, 0x81, 0x
 const LIFTING_ARM_CHARACTERISTIC_END_UUID: Uuid = Uuid::from_bytes([0x00, 0x81, 0x19, 0x14, 0x45, 0x11, 0x19, 0x19, 0x19, 0x19, 0x45, 0x11, 0xE3, 0xD7, 0xA9, 0xAA]);
 const MCLAW_SWITCH_CHARACTERISTIC_UUID: Uuid = Uuid::from_bytes([0x00, 0x81, 0x19, 0x14, 0x45, 0x11, 0x19, 0x19, 0x19, 0x19, 0x45, 0x11, 0xC4, 0xD4, 0xD3, 0xE2]);
 const ROLE_CHARACTERISTIC_UUID: Uuid = Uuid::from_bytes([0x00, 0x81, 0x19, 0x14, 0x45, 0x11, 0x19, 0x19, 0x19, 0x19, 0x45, 0x11, 0x00, 0x00, 0x00, 0x91]);
+// ! "heartbeat" is the default, alias::known_characteristics resolves it through set_heartbeat_characteristic
+const KNOWN_CHARACTERISTICS: [(&str, Uuid); 11] = [
+    ("x_axis", X_CHARACTERISTIC_UUID),
+    ("y_axis", Y_CHARACTERISTIC_UUID),
+    ("r_axis", R_CHARACTERISTIC_UUID),
+    ("controller_usable", CONTROLLER_USABLE_CHARACTERISTIC_UUID),
+    ("lift_a", LIFTING_ARM_CHARACTERISTIC_A_UUID),
+    ("lift_b", LIFTING_ARM_CHARACTERISTIC_B_UUID),
+    ("lift_c", LIFTING_ARM_CHARACTERISTIC_C_UUID),
+    ("lift_end", LIFTING_ARM_CHARACTERISTIC_END_UUID),
+    ("claw", MCLAW_SWITCH_CHARACTERISTIC_UUID),
+    ("role", ROLE_CHARACTERISTIC_UUID),
+    ("heartbeat", heartbeat::HEARTBEAT_CHARACTERISTIC_UUID),
+];
+const UUID_BASE_PREFIX_LEN: usize = 12; // Leading bytes shared by the service and all characteristic UUIDs
 const DEVICE_ADDRESS: &str = "3c:0f:02:d1:e2:56"; // Default MAC address of the target device
 const MAXIUM_DISCOVER_PERIOD: u64 = 20000; // 20 seconds timeout for scanning
+const DEFAULT_ATT_MTU: u16 = 23; // Minimum ATT MTU guaranteed by the BLE spec
+const ATT_HEADER_SIZE: u16 = 3; // ATT header takes 3 bytes of every packet
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ArmData {
     pub x: u16,
     pub y: u16,
@@ -39,57 +101,628 @@ pub struct ArmData {
     pub controller_usable: bool,
 }
 
+impl ArmData {
+    /*
+        Axis values travel as 2-byte little endian but only the low byte is meaningful,
+        so anything above 0xFF means a corrupted read.
+    */
+    pub fn new(x: u16, y: u16, r: u16, controller_usable: bool) -> Result<Self, BleError> {
+        for (axis, value) in [("X", x), ("Y", y), ("R", r)] {
+            if value > u8::MAX as u16 {
+                return Err(BleError::InvalidPayload(format!("{} value 0x{:04X} exceeds 0xFF", axis, value)));
+            }
+        }
+        Ok(Self { x, y, r, controller_usable })
+    }
+}
+
+/*
+    Position of all arm axes as last sent to the device.
+    Used for the home position and for the last sent arm state.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArmPosition {
+    pub x: u8,
+    pub y: u8,
+    pub r: u8,
+    pub lifting_arm: u8,
+}
+
+impl Default for ArmPosition {
+    fn default() -> Self {
+        Self {
+            x: JOYSTICK_ZERO_VALUE,
+            y: JOYSTICK_ZERO_VALUE,
+            r: JOYSTICK_ZERO_VALUE,
+            lifting_arm: 0x00,
+        }
+    }
+}
+
+// Lifting arm channel tracked by ArmPosition::lifting_arm
+const PRIMARY_LIFTING_ARM_CHANNEL: &str = "A";
+
 const JOYSTICK_ZERO_VALUE: u8 = 0x7F;
 const CONTROLLER_USABLE: u8 = 0x01;
 const CONTROLLER_NOT_USABLE: u8 = 0x00;
 
+/*
+    Power/range tradeoff of device discovery.
+    tauri_plugin_blec does not expose the platform scan duty cycle, so the mode maps to the scan window:
+    low_power gives up after 10 s, balanced after 20 s and high_duty keeps scanning for 60 s.
+    "high_duty" finds slow advertisers sooner but increases battery drain on mobile.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanMode {
+    LowPower,
+    Balanced,
+    HighDuty,
+}
+
+impl ScanMode {
+    fn discover_period(&self) -> u64 {
+        match self {
+            ScanMode::LowPower => 10000,
+            ScanMode::Balanced => MAXIUM_DISCOVER_PERIOD,
+            ScanMode::HighDuty => 60000,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+fn parse_endian(endian: &str) -> Result<Endian, String> {
+    match endian.to_lowercase().as_str() {
+        "little" => Ok(Endian::Little),
+        "big" => Ok(Endian::Big),
+        _ => Err(format!("Unsupported byte order: {}", endian)),
+    }
+}
+
+/*
+    Wire encoding of a single joystick axis.
+    `width` is the number of bytes sent, the value byte is zero-padded according to `endian`.
+    Default is the current firmware format: 2 bytes, little endian ([value, 0x00]).
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AxisEncoding {
+    pub width: u8,
+    pub endian: Endian,
+}
+
+impl Default for AxisEncoding {
+    fn default() -> Self {
+        Self { width: 2, endian: Endian::Little }
+    }
+}
+
+const AXIS_X: usize = 0;
+const AXIS_Y: usize = 1;
+const AXIS_R: usize = 2;
+const MAXIUM_AXIS_WIDTH: u8 = 4;
+// Characteristic aliases of X, Y and R, indexed by AXIS_*
+const AXIS_ALIASES: [&str; 3] = ["x_axis", "y_axis", "r_axis"];
+
+fn encode_axis(value: u8, enc: &AxisEncoding) -> Vec<u8> {
+    let width = enc.width.max(1) as usize;
+    let mut data = vec![0x00; width];
+    match enc.endian {
+        Endian::Little => data[0] = value,
+        Endian::Big => data[width - 1] = value,
+    }
+    data
+}
+
+/*
+    Decode axis data written by encode_axis, `width` bytes in `endian` order.
+*/
+fn decode_axis(data: &[u8], enc: &AxisEncoding) -> Result<u16, BleError> {
+    let width = enc.width.max(1) as usize;
+    if data.len() != width {
+        return Err(BleError::InvalidPayload(format!("Expected {} bytes, got {}", width, data.len())));
+    }
+    let value = match enc.endian {
+        Endian::Little => data.iter().rev().fold(0u64, |acc, byte| acc << 8 | *byte as u64),
+        Endian::Big => data.iter().fold(0u64, |acc, byte| acc << 8 | *byte as u64),
+    };
+    u16::try_from(value).map_err(|_| BleError::InvalidPayload(format!("Axis value {} out of range", value)))
+}
+
+/*
+    Encode a single byte value as 2-byte characteristic data in the configured byte order.
+*/
+fn encode_value(state: &AppState, value: u8) -> Vec<u8> {
+    encode_axis(value, &AxisEncoding { width: 2, endian: *lock(&state.data_endianness) })
+}
+
+/*
+    Wire format expected by a characteristic: the axis encoding for X/Y/R, 2 bytes in data_endianness otherwise.
+*/
+fn wire_format(state: &AppState, char_uuid: Uuid) -> AxisEncoding {
+    let encoding = *lock(&state.axis_encoding);
+    match AXIS_ALIASES.iter().position(|alias| alias::characteristic_by_alias(state, alias).ok() == Some(char_uuid)) {
+        Some(axis) => encoding[axis],
+        None => AxisEncoding { width: 2, endian: *lock(&state.data_endianness) },
+    }
+}
+
+/*
+    Catch obviously wrong payloads before the BLE stack turns them into a cryptic error.
+    Empty and oversized payloads are rejected, a length or padding mismatch with `format` is only logged.
+*/
+fn validate_payload(data: &[u8], format: AxisEncoding, mtu: u16) -> Result<(), BleError> {
+    if data.is_empty() {
+        return Err(BleError::InvalidPayload("Empty data".to_string()));
+    }
+    let max_len = mtu.saturating_sub(ATT_HEADER_SIZE) as usize;
+    if data.len() > max_len {
+        return Err(BleError::InvalidPayload(format!("{} bytes exceed the {} bytes allowed by MTU {}", data.len(), max_len, mtu)));
+    }
+
+    if data.len() != format.width as usize {
+        info!("validate_payload: {:?} is {} byte(s), characteristic expects {}", data, data.len(), format.width);
+    } else if format.width == 2 && format.endian == Endian::Little && data[1] != 0x00 {
+        info!("validate_payload: second byte of {:?} is not 0x00", data);
+    }
+    Ok(())
+}
+
+/*
+    Decode characteristic data in the given byte order.
+    A single byte is taken as the value, otherwise the first two bytes are used. Empty data decodes as 0.
+*/
+pub fn parse_received_bytes(data: &[u8], endian: Endian) -> u16 {
+    match (data, endian) {
+        ([], _) => 0,
+        ([value], _) => *value as u16,
+        ([low, high, ..], Endian::Little) => u16::from_le_bytes([*low, *high]),
+        ([high, low, ..], Endian::Big) => u16::from_be_bytes([*high, *low]),
+    }
+}
+
+/*
+    Plugin handler fetched on first use and cached until the next disconnect.
+*/
+#[derive(Default)]
+pub struct BleHandler(Mutex<Option<&'static Handler>>);
+
+impl BleHandler {
+    fn get(&self) -> Result<&'static Handler, BleError> {
+        let mut cached = lock(&self.0);
+        if let Some(handler) = *cached {
+            return Ok(handler);
+        }
+        let handler = tauri_plugin_blec::get_handler()
+            .map_err(|e| report(BleError::HandlerUnavailable(e.to_string())))?;
+        *cached = Some(handler);
+        Ok(handler)
+    }
+
+    pub(crate) fn invalidate(&self) {
+        *lock(&self.0) = None;
+    }
+}
+
 pub struct AppState {
+    pub ble_handler: BleHandler,
     pub is_connected: Mutex<bool>,
     pub connected_address: Mutex<Option<String>>,
+    pub active_service_uuid: Mutex<Uuid>,
     pub controller_usable: Mutex<bool>,
+    pub axis_encoding: Mutex<[AxisEncoding; 3]>,
+    pub axis_calibration: Mutex<[Option<position::ArmRangeCalibration>; 3]>,
+    pub data_endianness: Mutex<Endian>,
+    pub script_running: AtomicBool,
+    pub script_stop: AtomicBool,
+    pub cancel_sequence: AtomicBool,
+    pub script_step: AtomicUsize,
+    pub script_total_steps: AtomicUsize,
+    pub home_position: Mutex<ArmPosition>,
+    pub last_arm_state: Mutex<ArmPosition>,
+    pub lifting_arm_range: Mutex<(u8, u8)>,
+    pub workspace_limits: Mutex<workspace::WorkspaceLimits>,
+    pub enforce_workspace: Mutex<bool>,
+    pub write_order: Mutex<Vec<String>>,
+    pub on_ready_script: Mutex<Option<Vec<script::ScriptStep>>>,
+    pub session: Mutex<session::SessionTracker>,
+    pub connection_intervals: Mutex<Vec<u64>>,
+    pub subscriptions: Mutex<HashMap<Uuid, subscription::Subscription>>,
+    pub dropped_subscriptions: Mutex<HashMap<Uuid, subscription::Subscription>>,
+    pub notification_thresholds: Mutex<HashMap<Uuid, u8>>,
+    pub start_auto_resubscribe: Mutex<bool>,
+    pub joystick_deadzone: Mutex<f32>,
+    pub deadband_shape: Mutex<joystick::DeadbandShape>,
+    pub joystick_curve: Mutex<joystick::JoystickCurve>,
+    pub supports_queued_writes: Mutex<Option<bool>>,
+    pub max_log_files: Mutex<usize>,
+    pub max_log_size_bytes: Mutex<u64>,
+    pub relative_step_size: Mutex<u8>,
+    pub joystick_target: Mutex<joystick::JoystickInput>,
+    pub coalesce_joystick_writes: Mutex<bool>,
+    pub joystick_coalescer: Mutex<joystick::JoystickCoalescer>,
+    pub joystick_poll_task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    pub background_write_tx: Mutex<Option<mpsc::Sender<queue::BackgroundWrite>>>,
+    pub background_write_task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    pub frame_throttle: Mutex<rate_limit::FrameThrottle>,
+    pub frame_refill_task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    pub emit_drop_events: Mutex<bool>,
+    pub queue_depth_history: Mutex<VecDeque<queue::QueueSnapshot>>,
+    pub queue_stats_capacity: Mutex<usize>,
+    pub queue_stats_task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    pub auto_reconnect_on_write_fail: Mutex<bool>,
+    pub reconnect_strategy: Mutex<reconnect::ReconnectStrategy>,
+    pub expected_disconnect: AtomicBool,
+    pub auto_retry_writes: AtomicU64,
+    pub dev_mode: Mutex<bool>,
+    pub debug_subscriptions: Mutex<Vec<Uuid>>,
+    pub last_disconnect_reason: Mutex<Option<disconnect::DisconnectReason>>,
+    pub negotiated_mtu: Mutex<u16>,
+    pub connection_params: Mutex<Option<connection_params::ConnectionParams>>,
+    pub connection_mode: Mutex<connection_mode::ConnectionMode>,
+    pub disconnect_after_ms: Mutex<u64>,
+    pub idle_disconnect_task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    pub claw_mode: Mutex<claw::ClawMode>,
+    pub claw_closed: Mutex<bool>,
+    pub claw_hold_duration_ms: Mutex<u64>,
+    pub claw_release_task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    pub heartbeat_characteristic: Mutex<Uuid>,
+    pub heartbeat_task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    pub active_tasks: Mutex<HashMap<String, tasks::TaskHandle>>,
+    pub quality_monitor: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    pub default_write_type: Mutex<String>,
+    pub characteristic_write_types: Mutex<HashMap<Uuid, String>>,
+    pub writes_paused: Mutex<bool>,
+    pub verbose_events: Mutex<bool>,
+    pub r_axis_semantics: Mutex<joystick::RAxisSemantics>,
+    pub use_notification_if_available: Mutex<bool>,
+    pub controller_status_poll_interval_ms: Mutex<u64>,
+    pub controller_status_task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    pub is_scanning: AtomicBool,
+    pub preload_in_progress: AtomicBool,
+    pub is_shutting_down: AtomicBool,
+    // Held for the whole shutdown_cleanup run, a later caller waits on it until the first run finished
+    pub shutdown_lock: tokio::sync::Mutex<()>,
+    pub lifting_arm_transition_active: AtomicBool,
+    pub scan_mode: Mutex<ScanMode>,
+    pub last_error: Mutex<Option<BleError>>,
+    pub rate_limiter: Mutex<rate_limit::RateLimiter>,
+    pub adaptive_rate_limit: Mutex<bool>,
+    pub write_queue_depth: AtomicUsize,
+    pub pending_write_count: AtomicI64,
+    pub queue_pressure_high: AtomicBool,
+    pub error_count: AtomicU64,
+    pub low_battery_threshold: Mutex<u8>,
+    pub low_battery_mode: AtomicBool,
+    pub feature_compatibility: Mutex<Option<firmware::CompatibilityReport>>,
+}
+
+/*
+    Lock a state mutex, recovering the data if another thread panicked while holding it.
+    The state only holds plain values, so a poisoned lock is still consistent enough to use.
+*/
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/*
+    Service UUID of all reads and writes, SERVICE_UUID unless overridden by set_service_uuid.
+*/
+pub(crate) fn service_uuid(state: &AppState) -> Uuid {
+    *lock(&state.active_service_uuid)
+}
+
+// Reject writes while pause_writes is in effect
+pub(crate) fn ensure_writes_allowed(state: &AppState) -> Result<(), BleError> {
+    if *lock(&state.writes_paused) {
+        Err(report(BleError::WritesPaused))
+    } else {
+        Ok(())
+    }
+}
+
+impl AppState {
+    pub(crate) fn handler(&self) -> Result<&'static Handler, BleError> {
+        self.ble_handler.get()
+    }
+
+    /*
+        Reset every field to its default in place, so test fixtures can share one AppState.
+        Background tasks are aborted.
+    */
+    #[cfg(test)]
+    pub fn reset_for_testing(&self) {
+        macro_rules! reset {
+            ($fresh:ident; mutex: $($m:ident),*; atomic: $($a:ident),*) => {
+                $( *lock(&self.$m) = $fresh.$m.into_inner().unwrap_or_else(std::sync::PoisonError::into_inner); )*
+                $( self.$a.store($fresh.$a.into_inner(), Ordering::SeqCst); )*
+            };
+        }
+
+        let tasks = [
+            &self.quality_monitor,
+            &self.controller_status_task,
+            &self.joystick_poll_task,
+            &self.background_write_task,
+            &self.frame_refill_task,
+            &self.queue_stats_task,
+            &self.idle_disconnect_task,
+            &self.claw_release_task,
+            &self.heartbeat_task,
+        ];
+        for task in tasks {
+            if let Some(handle) = lock(task).take() {
+                handle.abort();
+            }
+        }
+        self.ble_handler.invalidate();
+
+        let fresh = AppState::default();
+        reset!(fresh;
+            mutex: is_connected, connected_address, active_service_uuid, controller_usable, axis_encoding, axis_calibration, data_endianness, home_position, last_arm_state,
+                lifting_arm_range, workspace_limits, enforce_workspace, write_order, on_ready_script, session, connection_intervals, subscriptions, dropped_subscriptions, notification_thresholds, start_auto_resubscribe, joystick_deadzone, deadband_shape, joystick_curve, supports_queued_writes,
+                max_log_files, max_log_size_bytes,
+                relative_step_size, joystick_target, coalesce_joystick_writes, joystick_coalescer, joystick_poll_task, background_write_tx,
+                background_write_task, frame_throttle, frame_refill_task, emit_drop_events,
+                queue_depth_history, queue_stats_capacity, queue_stats_task, auto_reconnect_on_write_fail, reconnect_strategy, dev_mode, debug_subscriptions, last_disconnect_reason, negotiated_mtu, connection_params, connection_mode,
+                disconnect_after_ms, idle_disconnect_task, claw_mode, claw_closed, claw_hold_duration_ms, claw_release_task,
+                heartbeat_characteristic, heartbeat_task, active_tasks, quality_monitor,
+                default_write_type, characteristic_write_types, writes_paused, verbose_events, r_axis_semantics, use_notification_if_available,
+                controller_status_poll_interval_ms, controller_status_task, scan_mode, last_error,
+                rate_limiter, adaptive_rate_limit, low_battery_threshold, feature_compatibility;
+            atomic: script_running, script_stop, cancel_sequence, script_step, script_total_steps, auto_retry_writes,
+                preload_in_progress, is_shutting_down, expected_disconnect, is_scanning, lifting_arm_transition_active, write_queue_depth, pending_write_count, queue_pressure_high,
+                error_count, low_battery_mode);
+    }
+}
+
+/*
+    Reject BLE operations early when no device is connected,
+    instead of letting the plugin fail with a less helpful error.
+    A link dropped during an open session is restored first if auto reconnect on write failure is enabled.
+    On demand mode keeps is_connected set while the link is down and restores it in ensure_link.
+*/
+pub(crate) async fn ensure_connected(state: &AppState) -> Result<(), BleError> {
+    if *lock(&state.is_connected) {
+        return Ok(());
+    }
+    // ? connected_address outlives a dropped link, it is only cleared by disconnect
+    let session_open = lock(&state.connected_address).is_some();
+    if !session_open || !*lock(&state.auto_reconnect_on_write_fail) {
+        return Err(report(BleError::NotConnected));
+    }
+
+    info!("ensure_connected: link lost, reconnecting");
+    reconnect::reconnect_with_backoff(state).await.map_err(report)
+}
+
+/*
+    Holds an AtomicBool flag set while alive and clears it on drop,
+    so the flag is reset on every return path.
+*/
+pub(crate) struct FlagGuard<'a>(&'a AtomicBool);
+
+impl<'a> FlagGuard<'a> {
+    // Returns None if the flag is already set by someone else
+    pub(crate) fn acquire(flag: &'a AtomicBool) -> Option<Self> {
+        flag.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .ok()
+            .map(|_| Self(flag))
+    }
+}
+
+impl Drop for FlagGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
+            ble_handler: BleHandler::default(),
             is_connected: Mutex::new(false),
             connected_address: Mutex::new(None),
+            active_service_uuid: Mutex::new(SERVICE_UUID),
             controller_usable: Mutex::new(false),
+            axis_encoding: Mutex::new([AxisEncoding::default(); 3]),
+            axis_calibration: Mutex::new([None; 3]),
+            data_endianness: Mutex::new(Endian::Little),
+            script_running: AtomicBool::new(false),
+            script_stop: AtomicBool::new(false),
+            cancel_sequence: AtomicBool::new(false),
+            script_step: AtomicUsize::new(0),
+            script_total_steps: AtomicUsize::new(0),
+            home_position: Mutex::new(ArmPosition::default()),
+            last_arm_state: Mutex::new(ArmPosition::default()),
+            lifting_arm_range: Mutex::new((0, u8::MAX)),
+            workspace_limits: Mutex::new(workspace::WorkspaceLimits::default()),
+            enforce_workspace: Mutex::new(false),
+            write_order: Mutex::new(Vec::new()),
+            on_ready_script: Mutex::new(None),
+            session: Mutex::new(session::SessionTracker::default()),
+            connection_intervals: Mutex::new(Vec::new()),
+            subscriptions: Mutex::new(HashMap::new()),
+            dropped_subscriptions: Mutex::new(HashMap::new()),
+            notification_thresholds: Mutex::new(HashMap::new()),
+            start_auto_resubscribe: Mutex::new(false),
+            joystick_deadzone: Mutex::new(0.0),
+            deadband_shape: Mutex::new(joystick::DeadbandShape::Circular),
+            joystick_curve: Mutex::new(joystick::JoystickCurve::default()),
+            supports_queued_writes: Mutex::new(None),
+            max_log_files: Mutex::new(logs::DEFAULT_MAX_LOG_FILES),
+            max_log_size_bytes: Mutex::new(logs::DEFAULT_MAX_LOG_SIZE_BYTES),
+            relative_step_size: Mutex::new(joystick::DEFAULT_RELATIVE_STEP_SIZE),
+            joystick_target: Mutex::new(joystick::JoystickInput::default()),
+            coalesce_joystick_writes: Mutex::new(false),
+            joystick_coalescer: Mutex::new(joystick::JoystickCoalescer::default()),
+            joystick_poll_task: Mutex::new(None),
+            background_write_tx: Mutex::new(None),
+            background_write_task: Mutex::new(None),
+            frame_throttle: Mutex::new(rate_limit::FrameThrottle::default()),
+            frame_refill_task: Mutex::new(None),
+            emit_drop_events: Mutex::new(false),
+            queue_depth_history: Mutex::new(VecDeque::new()),
+            queue_stats_capacity: Mutex::new(queue::DEFAULT_QUEUE_STATS_CAPACITY),
+            queue_stats_task: Mutex::new(None),
+            auto_reconnect_on_write_fail: Mutex::new(false),
+            reconnect_strategy: Mutex::new(reconnect::ReconnectStrategy::None),
+            expected_disconnect: AtomicBool::new(false),
+            auto_retry_writes: AtomicU64::new(0),
+            dev_mode: Mutex::new(false),
+            debug_subscriptions: Mutex::new(Vec::new()),
+            last_disconnect_reason: Mutex::new(None),
+            negotiated_mtu: Mutex::new(DEFAULT_ATT_MTU),
+            connection_params: Mutex::new(None),
+            connection_mode: Mutex::new(connection_mode::ConnectionMode::Persistent),
+            disconnect_after_ms: Mutex::new(connection_mode::DEFAULT_DISCONNECT_AFTER_MS),
+            idle_disconnect_task: Mutex::new(None),
+            claw_mode: Mutex::new(claw::ClawMode::Toggle),
+            claw_closed: Mutex::new(false),
+            claw_hold_duration_ms: Mutex::new(claw::DEFAULT_CLAW_HOLD_MS),
+            claw_release_task: Mutex::new(None),
+            heartbeat_characteristic: Mutex::new(heartbeat::HEARTBEAT_CHARACTERISTIC_UUID),
+            heartbeat_task: Mutex::new(None),
+            active_tasks: Mutex::new(HashMap::new()),
+            quality_monitor: Mutex::new(None),
+            default_write_type: Mutex::new(WRITE_TYPE_WITHOUT_RESPONSE.to_string()),
+            characteristic_write_types: Mutex::new(HashMap::new()),
+            writes_paused: Mutex::new(false),
+            verbose_events: Mutex::new(false),
+            r_axis_semantics: Mutex::new(joystick::RAxisSemantics::Joystick),
+            use_notification_if_available: Mutex::new(true),
+            controller_status_poll_interval_ms: Mutex::new(status::DEFAULT_STATUS_POLL_INTERVAL_MS),
+            controller_status_task: Mutex::new(None),
+            is_scanning: AtomicBool::new(false),
+            preload_in_progress: AtomicBool::new(false),
+            is_shutting_down: AtomicBool::new(false),
+            shutdown_lock: tokio::sync::Mutex::new(()),
+            lifting_arm_transition_active: AtomicBool::new(false),
+            scan_mode: Mutex::new(ScanMode::Balanced),
+            last_error: Mutex::new(None),
+            rate_limiter: Mutex::new(rate_limit::RateLimiter::default()),
+            adaptive_rate_limit: Mutex::new(false),
+            write_queue_depth: AtomicUsize::new(0),
+            pending_write_count: AtomicI64::new(0),
+            queue_pressure_high: AtomicBool::new(false),
+            error_count: AtomicU64::new(0),
+            low_battery_threshold: Mutex::new(battery::DEFAULT_LOW_BATTERY_THRESHOLD),
+            low_battery_mode: AtomicBool::new(false),
+            feature_compatibility: Mutex::new(None),
         }
     }
 }
 
+/*
+    Validate a MAC address given as 3C:0F:02:D1:D3:8A, 3c:0f:02:d1:d3:8a, 3C-0F-02-D1-D3-8A or 3c0f02d1d38a
+    and return it in the canonical uppercase, colon separated form.
+*/
+pub(crate) fn normalize_mac_address(addr: &str) -> Result<String, BleError> {
+    let addr = addr.trim();
+    // ? Mixed separators are rejected, the second one is left in and fails the hex check
+    let separator = addr.chars().find(|c| *c == ':' || *c == '-');
+    let hex = addr.chars().filter(|c| Some(*c) != separator).collect::<String>().to_uppercase();
+    let valid = hex.len() == 12
+        && hex.chars().all(|c| c.is_ascii_hexdigit())
+        && separator.is_none_or(|separator| addr.split(separator).all(|octet| octet.len() == 2));
+    if !valid {
+        return Err(BleError::InvalidArgument(format!("Invalid MAC address: {}", addr)));
+    }
+    Ok((0..6).map(|i| &hex[i * 2..i * 2 + 2]).collect::<Vec<_>>().join(":"))
+}
+
 #[tauri::command]
 async fn set_connected_device_address(state: tauri::State<'_, AppState>, address: String) -> Result<String, String> {
-    let mut addr = state.connected_address.lock().unwrap();
+    let mut addr = lock(&state.connected_address);
     if address.is_empty() {
         *addr = None;
         return Ok("Connected device address cleared.".to_string());
     } else {
-        *addr = Some(address);
+        *addr = Some(normalize_mac_address(&address)?);
         return Ok(format!("Connected device address {:?} set.", addr));
     }
 }
 
 #[tauri::command]
 async fn get_connected_device_address(state: tauri::State<'_, AppState>) -> Result<String, String> {
-    let addr = state.connected_address.lock().unwrap();
+    let addr = lock(&state.connected_address);
     Ok(addr.clone().unwrap_or("No device connected".to_string()))
 }
 
 #[tauri::command]
 async fn set_controller_usable(state: tauri::State<'_, AppState>, usable: bool) -> Result<String, String> {
-    let mut controller_usable = state.controller_usable.lock().unwrap();
+    let mut controller_usable = lock(&state.controller_usable);
     *controller_usable = usable;
     Ok(format!("Controller usable state set to: {}", usable))
 }
 
 #[tauri::command]
 async fn get_controller_usable(state: tauri::State<'_, AppState>) -> Result<bool, String> {
-    let controller_usable = state.controller_usable.lock().unwrap();
+    let controller_usable = lock(&state.controller_usable);
     Ok(*controller_usable)
 }
 
+/*
+    Configure wire encoding of one joystick axis at runtime.
+    axis: 0 = X, 1 = Y, 2 = R. width: 1 to 4 bytes. endian: "little" or "big".
+    The byte order holds until the next set_data_endianness, which resets it on every axis.
+*/
+#[tauri::command]
+async fn set_axis_encoding(state: tauri::State<'_, AppState>, axis: u8, width: u8, endian: String) -> Result<String, String> {
+    let index = axis as usize;
+    if index > AXIS_R {
+        return Err(format!("Unsupported axis: {}", axis));
+    }
+    if width == 0 || width > MAXIUM_AXIS_WIDTH {
+        return Err(format!("Unsupported axis data width: {}", width));
+    }
+    let endian = parse_endian(&endian)?;
+
+    let mut encoding = lock(&state.axis_encoding);
+    encoding[index] = AxisEncoding { width, endian };
+    Ok(format!("Axis {} encoding set to {} byte(s), {:?} endian", axis, width, endian))
+}
+
+/*
+    Use another service UUID for all reads and writes, e.g. for test firmware.
+    Characteristic UUIDs are unchanged. reset_service_uuid restores SERVICE_UUID.
+*/
+#[tauri::command]
+async fn set_service_uuid(state: tauri::State<'_, AppState>, uuid: String) -> Result<String, BleError> {
+    let uuid = subscription::parse_uuid(&uuid)?;
+    *lock(&state.active_service_uuid) = uuid;
+    info!("Active service UUID set to {}", uuid);
+    Ok(format!("Service UUID set to: {}", uuid))
+}
+
+#[tauri::command]
+async fn reset_service_uuid(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    *lock(&state.active_service_uuid) = SERVICE_UUID;
+    Ok(format!("Service UUID reset to: {}", SERVICE_UUID))
+}
+
+/*
+    Switch byte order for firmware variants expecting big endian [0x00, value] data.
+    Applies to lifting arm and arm command writes, decoding of read values and the byte order of all joystick axes.
+    ! Bulk setter: overrides the per-axis byte order set with set_axis_encoding, call that afterwards for exceptions.
+    endian: "little" or "big".
+*/
+#[tauri::command]
+async fn set_data_endianness(state: tauri::State<'_, AppState>, endian: String) -> Result<String, String> {
+    let endian = parse_endian(&endian)?;
+    *lock(&state.data_endianness) = endian;
+    for encoding in lock(&state.axis_encoding).iter_mut() {
+        encoding.endian = endian;
+    }
+    info!("Data byte order set to {:?} endian on all axes", endian);
+    Ok(format!("Data byte order set to {:?} endian, per-axis byte order overridden", endian))
+}
+
 /*
     Poll controller usable status from device.
     Returns true if device is ready to receive joystick commands (0x01), false otherwise (0x00).
@@ -98,8 +731,9 @@ async fn get_controller_usable(state: tauri::State<'_, AppState>) -> Result<bool
 #[tauri::command]
 async fn poll_controller_status(state: tauri::State<'_, AppState>) -> Result<bool, String> {
     info!("Polling controller status...");
+    ensure_connected(&state).await?;
     
-    let data = receive_data(CONTROLLER_USABLE_CHARACTERISTIC_UUID, SERVICE_UUID)
+    let data = receive_data(state.clone(), CONTROLLER_USABLE_CHARACTERISTIC_UUID, service_uuid(&state))
         .await
         .map_err(|e| {
             info!("Failed to read controller status: {}", e);
@@ -109,24 +743,26 @@ async fn poll_controller_status(state: tauri::State<'_, AppState>) -> Result<boo
     info!("Received controller status data: {:?} (len={})", data, data.len());
     
     // Check the last byte for the actual value (handles both 1-byte and 2-byte formats)
-    let usable = if data.is_empty() {
-        info!("Controller status: empty data, treating as not usable");
-        false
-    } else {
+    let usable = match data.last() {
+        None => {
+            info!("Controller status: empty data, treating as not usable");
+            false
+        }
         // Get the last byte (handles [0x01] or [0x00, 0x01] formats)
-        let status_byte = *data.last().unwrap();
-        info!("Controller status byte: 0x{:02X}", status_byte);
+        Some(&status_byte) => {
+            info!("Controller status byte: 0x{:02X}", status_byte);
         
-        if status_byte == CONTROLLER_USABLE {
-            info!("Controller status: 0x01 (usable)");
-            true
-        } else if status_byte == CONTROLLER_NOT_USABLE {
-            info!("Controller status: 0x00 (not usable)");
-            false
-        } else {
-            // Unknown value, default to not usable
-            info!("Unknown controller status value: 0x{:02X}, treating as not usable", status_byte);
-            false
+            if status_byte == CONTROLLER_USABLE {
+                info!("Controller status: 0x01 (usable)");
+                true
+            } else if status_byte == CONTROLLER_NOT_USABLE {
+                info!("Controller status: 0x00 (not usable)");
+                false
+            } else {
+                // Unknown value, default to not usable
+                info!("Unknown controller status value: 0x{:02X}, treating as not usable", status_byte);
+                false
+            }
         }
     };
     
@@ -135,30 +771,90 @@ async fn poll_controller_status(state: tauri::State<'_, AppState>) -> Result<boo
     Ok(usable)
 }
 
+/*
+    Read the controller status characteristic and return the bytes unmodified, for debugging the firmware wire format.
+    Does not update the controller usable state.
+*/
+#[tauri::command]
+async fn read_controller_status_raw(state: tauri::State<'_, AppState>) -> Result<Vec<u8>, BleError> {
+    let data = receive_data(state.clone(), CONTROLLER_USABLE_CHARACTERISTIC_UUID, service_uuid(&state)).await?;
+    // Same interpretation as poll_controller_status, the last byte carries the value
+    let usable = data.last() == Some(&CONTROLLER_USABLE);
+    info!("Raw controller status: {:02X?} (len={}), interpreted as usable={}", data, data.len(), usable);
+    Ok(data)
+}
+
 /*
     Send joystick X and Y values to device.
     x and y should be in range 0x00 to 0xFF, with 0x7F being center/zero position.
-    Device expects 2-byte data format: [value, 0x00]
+    Device expects 2-byte data format: [value, 0x00] by default, see set_axis_encoding.
 */
 #[tauri::command]
 async fn send_joystick_data(state: tauri::State<'_, AppState>, x: u8, y: u8, r: u8) -> Result<String, String> {
+    ensure_connected(&state).await?;
+
     // Check if controller is usable before sending
     let usable = {
-        let controller_usable = state.controller_usable.lock().unwrap();
+        let controller_usable = lock(&state.controller_usable);
         *controller_usable
     };
     
     if !usable {
-        return Err("Controller is not usable, cannot send joystick data".to_string());
+        metrics::record_error(&state, "send_joystick_data");
+        return Err(report(BleError::ControllerNotUsable).into());
     }
-    
+
+    if !rate_limit::take_frame(&state, x, y, r) {
+        return Ok(format!("Joystick frame dropped by rate limit: X={}, Y={}, R={}", x, y, r));
+    }
+
+    if *lock(&state.coalesce_joystick_writes) {
+        return joystick::coalesce_frame(state, joystick::JoystickInput { x, y, r }).await;
+    }
+    write_joystick_frame(state, x, y, r).await
+}
+
+/*
+    Apply deadzone and workspace limits to one joystick frame and write X, Y and R in the configured write order.
+*/
+async fn write_joystick_frame(state: tauri::State<'_, AppState>, x: u8, y: u8, r: u8) -> Result<String, String> {
     // info!("Sending joystick data: X=0x{:02X}00, Y=0x{:02X}00, R=0x{:02X}00", x, y, r);
-    
-    let x_fut = write_data(X_CHARACTERISTIC_UUID, SERVICE_UUID, vec![x, 0x00]);
-    let y_fut = write_data(Y_CHARACTERISTIC_UUID, SERVICE_UUID, vec![y, 0x00]);
-    let r_fut = write_data(R_CHARACTERISTIC_UUID, SERVICE_UUID, vec![r, 0x00]);
 
-    let (x_res, y_res, r_res) = tokio::join!(x_fut, y_fut, r_fut);
+    let (x, y, r) = joystick::apply_deadzone(&state, x, y, r);
+    let x = workspace::clamp_to_workspace(&state, "x", x);
+    let y = workspace::clamp_to_workspace(&state, "y", y);
+    let r = workspace::clamp_to_workspace(&state, "r", r);
+
+    let encoding = *lock(&state.axis_encoding);
+
+    // Named spans show the time of each axis write in async task tooling
+    let x_fut = write_data(state.clone(), X_CHARACTERISTIC_UUID, service_uuid(&state), encode_axis(x, &encoding[AXIS_X]))
+        .instrument(info_span!("write_joystick_x"));
+    let y_fut = write_data(state.clone(), Y_CHARACTERISTIC_UUID, service_uuid(&state), encode_axis(y, &encoding[AXIS_Y]))
+        .instrument(info_span!("write_joystick_y"));
+    let r_fut = write_data(state.clone(), R_CHARACTERISTIC_UUID, service_uuid(&state), encode_axis(r, &encoding[AXIS_R]))
+        .instrument(info_span!("write_joystick_r"));
+
+    let order = lock(&state.write_order).clone();
+    let (x_res, y_res, r_res) = if order.is_empty() {
+        tokio::join!(x_fut, y_fut, r_fut)
+    } else {
+        // ? Futures only start when awaited, awaiting them one by one keeps the configured order
+        let mut pending = [Some(x_fut), Some(y_fut), Some(r_fut)];
+        let mut results = [Ok(String::new()), Ok(String::new()), Ok(String::new())];
+        let mut axes = [AXIS_X, AXIS_Y, AXIS_R];
+        axes.sort_by_key(|&axis| alias::write_rank(&order, AXIS_ALIASES[axis]));
+        for axis in axes {
+            if let Some(write) = pending[axis].take() {
+                results[axis] = write.await;
+            }
+        }
+        let [x_res, y_res, r_res] = results;
+        (x_res, y_res, r_res)
+    };
+    if x_res.is_err() || y_res.is_err() || r_res.is_err() {
+        metrics::record_error(&state, "send_joystick_data");
+    }
 
     if let Err(e) = x_res {
         // info!("Failed to write X value: {}", e);
@@ -172,12 +868,35 @@ async fn send_joystick_data(state: tauri::State<'_, AppState>, x: u8, y: u8, r:
         // info!("Failed to write R value: {}", e);
         return Err(format!("Failed to write R value: {}", e));
     }
+
+    {
+        let mut last = lock(&state.last_arm_state);
+        last.x = x;
+        last.y = y;
+        last.r = r;
+    }
     
     Ok(format!("Joystick data sent: X={}, Y={}, R={}", x, y, r))
 }
 
+/*
+    Write a lifting arm channel (A, B, C, END) or the claw.
+    Lifting arm values outside lifting_arm_range are rejected, this is a software safety limit only
+    and does not protect the arm from other writers such as send_raw_bytes.
+    With workspace enforcement enabled lifting arm values are clamped to the workspace first.
+*/
 #[tauri::command]
-async fn send_lifting_arm_value(channel: String, value: u8) -> Result<String, String> {
+async fn send_lifting_arm_value(state: tauri::State<'_, AppState>, channel: String, value: u8) -> Result<String, String> {
+    ensure_connected(&state).await?;
+
+    let value = if channel == "Claw" { value } else { workspace::clamp_to_workspace(&state, "lift", value) };
+    if channel != "Claw" {
+        let (min, max) = *lock(&state.lifting_arm_range);
+        if !(min..=max).contains(&value) {
+            return Err(report(BleError::OutOfRange { value, min, max }).into());
+        }
+    }
+
     let uuid = match channel.as_str() {
         "A" => LIFTING_ARM_CHARACTERISTIC_A_UUID,
         "B" => LIFTING_ARM_CHARACTERISTIC_B_UUID,
@@ -187,36 +906,142 @@ async fn send_lifting_arm_value(channel: String, value: u8) -> Result<String, St
         _ => return Err(format!("Unsupported lifting arm channel: {}", channel)),
     };
 
-    // Device expects 2-byte data format: [value, 0x00] - little endian
-    write_data(uuid, SERVICE_UUID, vec![value, 0x00])
+    // Device expects 2-byte data format: [value, 0x00] - little endian by default, see set_data_endianness
+    let data = encode_value(&state, value);
+    write_data(state.clone(), uuid, service_uuid(&state), data.clone())
         .await
         .map_err(|e| format!("Failed to write lifting arm {} value: {}", channel, e))?;
 
-    Ok(format!("Lifting arm {} value sent: 0x{:02X}00", channel, value))
+    if channel == PRIMARY_LIFTING_ARM_CHANNEL {
+        lock(&state.last_arm_state).lifting_arm = value;
+    }
+
+    Ok(format!("Lifting arm {} value sent: {:02X?}", channel, data))
 }
 
 #[tauri::command]
-async fn send_arm_command(command: String) -> Result<String, String> {
+async fn set_lifting_arm_range(state: tauri::State<'_, AppState>, min: u8, max: u8) -> Result<String, BleError> {
+    if min >= max {
+        return Err(BleError::InvalidArgument(format!("Lifting arm range min {} must be below max {}", min, max)));
+    }
+    *lock(&state.lifting_arm_range) = (min, max);
+    Ok(format!("Lifting arm range set to: {}..={}", min, max))
+}
+
+#[tauri::command]
+async fn send_arm_command(state: tauri::State<'_, AppState>, command: String) -> Result<String, String> {
+    ensure_connected(&state).await?;
+
     let (uuid, value) = match command.as_str() {
         "start" => (ROLE_CHARACTERISTIC_UUID, 0x91),
         _ => return Err(format!("Unsupported arm command: {}", command)),
     };
 
-    // Device expects 2-byte data format: [value, 0x00] - little endian
-    write_data(uuid, SERVICE_UUID, vec![value, 0x00])
+    // Device expects 2-byte data format: [value, 0x00] - little endian by default, see set_data_endianness
+    let data = encode_value(&state, value);
+    write_data(state.clone(), uuid, service_uuid(&state), data.clone())
         .await
         .map_err(|e| format!("Failed to write arm command {:?}: {}", command, e))?;
 
-    Ok(format!("Arm command '{}' sent with value 0x{:02X}00", command, value))
+    Ok(format!("Arm command '{}' sent with value {:02X?}", command, data))
+}
+
+/*
+    Center all joystick axes immediately.
+    Skips the controller usable check and always writes reliably (with response, retried) so a dropped stop is reported.
+    ! Writes straight to the handler, a stop must not wait behind the write queue or rate limiter it overrides.
+*/
+#[tauri::command]
+async fn emergency_stop(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    ensure_connected(&state).await?;
+    info!("Emergency stop requested");
+
+    let handler = state.handler()?;
+    let service = service_uuid(&state);
+    let encoding = *lock(&state.axis_encoding);
+    let x_data = encode_axis(JOYSTICK_ZERO_VALUE, &encoding[AXIS_X]);
+    let y_data = encode_axis(JOYSTICK_ZERO_VALUE, &encoding[AXIS_Y]);
+    let r_data = encode_axis(JOYSTICK_ZERO_VALUE, &encoding[AXIS_R]);
+    let reliable = policy::WritePolicy::reliable();
+    let x_fut = policy::write_with_policy(handler, X_CHARACTERISTIC_UUID, service, &x_data, &reliable);
+    let y_fut = policy::write_with_policy(handler, Y_CHARACTERISTIC_UUID, service, &y_data, &reliable);
+    let r_fut = policy::write_with_policy(handler, R_CHARACTERISTIC_UUID, service, &r_data, &reliable);
+
+    let (x_res, y_res, r_res) = tokio::join!(x_fut, y_fut, r_fut);
+    {
+        let mut session = lock(&state.session);
+        for res in [&x_res, &y_res, &r_res] {
+            session.record_write(res.is_ok());
+        }
+    }
+    x_res.map_err(|e| format!("Failed to stop X axis: {}", e))?;
+    y_res.map_err(|e| format!("Failed to stop Y axis: {}", e))?;
+    r_res.map_err(|e| format!("Failed to stop R axis: {}", e))?;
+
+    {
+        let mut last = lock(&state.last_arm_state);
+        last.x = JOYSTICK_ZERO_VALUE;
+        last.y = JOYSTICK_ZERO_VALUE;
+        last.r = JOYSTICK_ZERO_VALUE;
+    }
+    Ok("Emergency stop sent.".to_string())
+}
+
+/*
+    Read X, Y and R back from the device.
+*/
+#[tauri::command]
+async fn get_arm_data(state: tauri::State<'_, AppState>) -> Result<ArmData, BleError> {
+    ensure_connected(&state).await?;
+
+    let x = receive_data(state.clone(), X_CHARACTERISTIC_UUID, service_uuid(&state)).await?;
+    let y = receive_data(state.clone(), Y_CHARACTERISTIC_UUID, service_uuid(&state)).await?;
+    let r = receive_data(state.clone(), R_CHARACTERISTIC_UUID, service_uuid(&state)).await?;
+
+    let encoding = *lock(&state.axis_encoding);
+    ArmData::new(
+        decode_axis(&x, &encoding[AXIS_X])?,
+        decode_axis(&y, &encoding[AXIS_Y])?,
+        decode_axis(&r, &encoding[AXIS_R])?,
+        *lock(&state.controller_usable),
+    )
+}
+
+/*
+    Describe an axis value relative to the 0x7F center: "center", "+N%" or "-N%".
+*/
+fn format_axis(value: u16) -> String {
+    let zero = JOYSTICK_ZERO_VALUE as f32;
+    let offset = value as f32 - zero;
+    let percent = if offset >= 0.0 {
+        offset / (u8::MAX as f32 - zero) * 100.0
+    } else {
+        offset / zero * 100.0
+    };
+    match percent.round() as i32 {
+        0 => "center".to_string(),
+        p if p > 0 => format!("+{}%", p),
+        p => format!("{}%", p),
+    }
+}
+
+#[tauri::command]
+async fn format_arm_data(data: ArmData) -> Result<String, String> {
+    Ok(format!(
+        "X: {} ({}), Y: {} ({}), R: {} ({}), Controller: {}",
+        data.x, format_axis(data.x),
+        data.y, format_axis(data.y),
+        data.r, format_axis(data.r),
+        if data.controller_usable { "Ready" } else { "Not ready" },
+    ))
 }
 
 /*
     Stop scan device if some error occurred.
 */
 #[tauri::command]
-async fn stop_scan() -> Result<String, String> {
-    let handler = tauri_plugin_blec::get_handler()
-        .map_err(|e| format!("Get handle failed: {}", e))?;
+async fn stop_scan(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let handler = state.handler()?;
 
     handler
         .stop_scan()
@@ -231,15 +1056,26 @@ async fn stop_scan() -> Result<String, String> {
     Connect to device.
 */
 #[tauri::command]
-async fn connect(state: tauri::State<'_, AppState>, addr: &str) -> Result<String, String> {
+#[tracing::instrument(skip_all, fields(addr = %addr))]
+async fn connect(app: tauri::AppHandle, state: tauri::State<'_, AppState>, addr: &str) -> Result<String, String> {
+    let started = std::time::Instant::now();
+    let result = connect_device(app, state.clone(), addr).await;
+    events::log_operation(&state, "connect", None, None, result.is_ok(), started);
+    result
+}
+
+async fn connect_device(app: tauri::AppHandle, state: tauri::State<'_, AppState>, addr: &str) -> Result<String, String> {
     info!("connect() called with address: {}", addr);
+    let addr = &normalize_mac_address(addr)?;
     
     // Start scanning to find the device first
     info!("Starting scan to find device {}...", addr);
-    let mut rx = scan_with_monitor().await?;
+    let scan_guard = claim_scanner(&state)?;
+    let mut rx = scan_with_monitor(state.clone()).await?;
 
     let target_address = addr.to_uppercase();
     let mut found = false;
+    let mut found_rssi = None;
 
     // Monitor scan results
     while let Some(devices) = rx.recv().await {
@@ -247,6 +1083,7 @@ async fn connect(state: tauri::State<'_, AppState>, addr: &str) -> Result<String
             if device.address.to_uppercase() == target_address {
                 info!("Device {} found during scan, stopping scan...", target_address);
                 found = true;
+                found_rssi = device.rssi;
                 break;
             }
         }
@@ -256,30 +1093,49 @@ async fn connect(state: tauri::State<'_, AppState>, addr: &str) -> Result<String
     }
 
     // Stop scan regardless
-    let _ = stop_scan().await;
+    let _ = stop_scan(state.clone()).await;
+    drop(scan_guard);
 
     if !found {
-        return Err(format!("Device {} not found during scan", addr));
+        metrics::record_error(&state, "connect");
+        return Err(report(BleError::ScanTimeout(addr.to_string())).into());
     }
 
     info!("Attempting connection to {}...", addr);
 
-    let handler = tauri_plugin_blec::get_handler()
-        .map_err(|e| {
-            info!("connect: Get handle failed: {}", e);
-            format!("Get handle failed: {}", e)
-        })?;
+    let handler = state.handler()?;
 
-    match handler.connect(addr, OnDisconnectHandler::None, false).await {
+    match handler.connect(addr, reconnect::on_disconnect_handler(&state), false).await {
         Err(e) => {
             info!("connect: Connection failed: {}", e);
-            return Err(format!("Connect {:?} error occurred: {}", addr, e));
+            metrics::record_error(&state, "connect");
+            return Err(report(BleError::ConnectFailed { address: addr.to_string(), reason: e.to_string() }).into());
         }
         Ok(_) => {
             info!("connect: Connection successful, updating state...");
             set_connected_device_address(state.clone(), addr.to_string()).await?;
-            *state.is_connected.lock().unwrap() = true;
+            *lock(&state.is_connected) = true;
+            *lock(&state.supports_queued_writes) = None;
+            // ! A session still open from a connect without disconnect is recorded before the new one starts
+            session::finish_session(&app, &state);
+            {
+                // Every connection starts a fresh stats epoch
+                let mut session = lock(&state.session);
+                session.start();
+                if let Some(rssi) = found_rssi {
+                    session.record_rssi(rssi);
+                }
+            }
+            rate_limit::adapt_to_rssi(&state, found_rssi);
+            connection_params::reapply(&state);
             info!("connect: State updated");
+            verify_device_identity(app.clone(), state.clone()).await?;
+            // ? Devices without the Device Information Service keep every feature enabled
+            if let Err(e) = firmware::check_feature_compatibility(state.clone()).await {
+                info!("connect: Firmware compatibility check skipped: {}", e);
+            }
+            registry::record_connection(&app, addr);
+            subscription::resubscribe_all(&app, &state).await;
         }
     }
 
@@ -287,98 +1143,293 @@ async fn connect(state: tauri::State<'_, AppState>, addr: &str) -> Result<String
 }
 
 
+/*
+    Check that the connected device exposes the active service UUID, disconnect it if it doesn't.
+*/
+#[tauri::command]
+async fn verify_device_identity(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    ensure_connected(&state).await?;
+    let address = lock(&state.connected_address).clone().unwrap_or_default();
+
+    let handler = state.handler()?;
+    let services = handler
+        .discover_services(&address)
+        .await
+        .map_err(|e| report(BleError::Command(format!("Discover services of {} failed: {}", address, e))))?;
+
+    let service = service_uuid(&state);
+    if services.iter().any(|s| s.uuid == service) {
+        return Ok(());
+    }
+
+    info!("verify_device_identity: {} does not expose {}, disconnecting", address, service);
+    if let Err(e) = disconnect(app, state).await {
+        info!("verify_device_identity: disconnect failed: {}", e);
+    }
+    Err(report(BleError::DeviceIdentityMismatch {
+        address,
+        expected_service: service.to_string(),
+    }))
+}
+
 /*
     Disconnect from device.
     Before disconnecting, turn off the light and reset state.
 */
 #[tauri::command]
-async fn disconnect(state: tauri::State<'_, AppState>) -> Result<String, String> {
-    // Send zero values before disconnecting (2-byte format: [value, 0x00] - little endian)
+async fn disconnect(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let started = std::time::Instant::now();
+    let result = disconnect_device(app, state.clone()).await;
+    events::log_operation(&state, "disconnect", None, None, result.is_ok(), started);
+    result
+}
+
+async fn disconnect_device(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<String, String> {
+    // Send zero values before disconnecting (encoded per axis, 2-byte little endian by default)
     info!("Sending zero values before disconnect...");
-    if let Err(e) = write_data(X_CHARACTERISTIC_UUID, SERVICE_UUID, vec![JOYSTICK_ZERO_VALUE, 0x00]).await {
+    let encoding = *lock(&state.axis_encoding);
+    if let Err(e) = write_data(state.clone(), X_CHARACTERISTIC_UUID, service_uuid(&state), encode_axis(JOYSTICK_ZERO_VALUE, &encoding[AXIS_X])).await {
         info!("Failed to send X zero value: {}", e);
     }
-    if let Err(e) = write_data(Y_CHARACTERISTIC_UUID, SERVICE_UUID, vec![JOYSTICK_ZERO_VALUE, 0x00]).await {
+    if let Err(e) = write_data(state.clone(), Y_CHARACTERISTIC_UUID, service_uuid(&state), encode_axis(JOYSTICK_ZERO_VALUE, &encoding[AXIS_Y])).await {
         info!("Failed to send Y zero value: {}", e);
     }
-    if let Err(e) = write_data(R_CHARACTERISTIC_UUID, SERVICE_UUID, vec![JOYSTICK_ZERO_VALUE, 0x00]).await {
+    if let Err(e) = write_data(state.clone(), R_CHARACTERISTIC_UUID, service_uuid(&state), encode_axis(JOYSTICK_ZERO_VALUE, &encoding[AXIS_R])).await {
         info!("Failed to send R zero value: {}", e);
     }
+
+    subscription::cleanup_subscriptions(&app, &state).await;
     
-    let handler = tauri_plugin_blec::get_handler()
-        .map_err(|e| format!("Get handle failed: {}", e))?;
+    let handler = state.handler()?;
 
+    reconnect::expect_disconnect(&state, true);
     match handler.disconnect().await {
         Err(e) => {
-            return Err(format!("Disconnect failed: {}", e));
+            reconnect::expect_disconnect(&state, false);
+            let message = format!("Disconnect failed: {}", e);
+            disconnect::record_disconnect(&state, disconnect::DisconnectReason::Error(message.clone()));
+            return Err(message);
         }
         Ok(_) => {
             // ! Reset all state after successful disconnect
+            connection_mode::cancel_idle_disconnect(&state);
+            disconnect::record_disconnect(&state, disconnect::DisconnectReason::UserInitiated);
             set_connected_device_address(state.clone(), "".to_string()).await?;
-            *state.is_connected.lock().unwrap() = false;
+            *lock(&state.is_connected) = false;
             set_controller_usable(state.clone(), false).await?;
+            session::finish_session(&app, &state);
+            state.ble_handler.invalidate();
         }
     }
 
     Ok(format!("Disconnected device and reset state"))
 }
 
+const WRITE_TYPE_WITH_RESPONSE: &str = "with_response";
+const WRITE_TYPE_WITHOUT_RESPONSE: &str = "without_response";
+
+fn parse_write_type(write_type: &str) -> Result<WriteType, BleError> {
+    match write_type {
+        WRITE_TYPE_WITH_RESPONSE => Ok(WriteType::WithResponse),
+        WRITE_TYPE_WITHOUT_RESPONSE => Ok(WriteType::WithoutResponse),
+        _ => Err(BleError::InvalidArgument(format!("Unsupported write type: {}", write_type))),
+    }
+}
+
+/*
+    Set write type used by write_data: "with_response" or "without_response".
+    Characteristics with their own write type (set_characteristic_write_type) keep it.
+    emergency_stop always writes with response regardless of this setting.
+*/
 #[tauri::command]
-async fn write_data(char_uuid: Uuid, service: Uuid, data: Vec<u8>) -> Result<String, String> {
+async fn set_default_write_type(state: tauri::State<'_, AppState>, write_type: String) -> Result<String, BleError> {
+    parse_write_type(&write_type)?;
+    *lock(&state.default_write_type) = write_type.clone();
+    Ok(format!("Default write type set to: {}", write_type))
+}
+
+/*
+    Write type write_data uses for `char_uuid`: its override if set, the default write type otherwise.
+*/
+fn write_type_for(state: &AppState, char_uuid: Uuid) -> String {
+    match lock(&state.characteristic_write_types).get(&char_uuid) {
+        Some(write_type) => write_type.clone(),
+        None => lock(&state.default_write_type).clone(),
+    }
+}
+
+/*
+    Override the write type of one characteristic (alias or UUID), None removes the override.
+*/
+#[tauri::command]
+async fn set_characteristic_write_type(state: tauri::State<'_, AppState>, char_uuid: String, write_type: Option<String>) -> Result<String, BleError> {
+    let uuid = alias::resolve_characteristic(&state, &char_uuid)?;
+    match write_type {
+        Some(write_type) => {
+            parse_write_type(&write_type)?;
+            lock(&state.characteristic_write_types).insert(uuid, write_type.clone());
+            Ok(format!("Write type of {} set to: {}", alias::characteristic_label(uuid), write_type))
+        }
+        None => {
+            lock(&state.characteristic_write_types).remove(&uuid);
+            Ok(format!("Write type override of {} removed", alias::characteristic_label(uuid)))
+        }
+    }
+}
+
+/*
+    Write type write_data uses for a characteristic (alias or UUID): "with_response" or "without_response".
+*/
+#[tauri::command]
+async fn get_write_type(state: tauri::State<'_, AppState>, char_uuid: String) -> Result<String, BleError> {
+    let uuid = alias::resolve_characteristic(&state, &char_uuid)?;
+    Ok(write_type_for(&state, uuid))
+}
+
+/*
+    Block all regular writes, e.g. while the arm is repositioned by hand. emergency_stop still goes through.
+*/
+#[tauri::command]
+async fn pause_writes(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    *lock(&state.writes_paused) = true;
+    events::emit("ble://writes-paused", ());
+    Ok("Writes paused.".to_string())
+}
+
+#[tauri::command]
+async fn resume_writes(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    *lock(&state.writes_paused) = false;
+    events::emit("ble://writes-resumed", ());
+    Ok("Writes resumed.".to_string())
+}
+
+#[tauri::command]
+async fn write_data(state: tauri::State<'_, AppState>, char_uuid: Uuid, service: Uuid, data: Vec<u8>) -> Result<String, String> {
+    ensure_writes_allowed(&state)?;
+    let write_type = write_type_for(&state, char_uuid);
+    write_data_as(state, char_uuid, service, data, policy::WritePolicy::once(parse_write_type(&write_type)?)).await
+}
+
+async fn write_data_as(state: tauri::State<'_, AppState>, char_uuid: Uuid, service: Uuid, data: Vec<u8>, policy: policy::WritePolicy) -> Result<String, String> {
+    let started = std::time::Instant::now();
+    // ? Only copy the payload when it is going to be logged
+    let logged = events::verbose(&state).then(|| data.clone());
+    let result = write_characteristic(state.clone(), char_uuid, service, data, policy).await;
+    events::log_operation(&state, "write", Some(char_uuid), logged.as_deref(), result.is_ok(), started);
+    result
+}
+
+async fn write_characteristic(state: tauri::State<'_, AppState>, char_uuid: Uuid, service: Uuid, data: Vec<u8>, policy: policy::WritePolicy) -> Result<String, String> {
     // info!("write_data called - Characteristic: {}, Service: {}, Data: {:?}", char_uuid, service, data);
+    let _pending = queue::PendingWrite::start(&state);
     
-    let handler = tauri_plugin_blec::get_handler()
-        .map_err(|e| {
-            // info!("write_data: Get handle failed: {}", e);
-            format!("Get handle failed: {}", e)
-        })?;
+    validate_payload(&data, wire_format(&state, char_uuid), *lock(&state.negotiated_mtu)).map_err(report)?;
+    let handler = state.handler()?;
 
-    handler
-        .send_data(char_uuid, Some(service), &data, WriteType::WithoutResponse)
-        .await
-        .map_err(|e| {
-            // info!("write_data: Send failed - Char: {}, Service: {}, Data: {:?}, Error: {}", char_uuid, service, data, e);
-            format!("Send {:?} to {:?} (Service: {:?}) failed: {}", data, char_uuid, service, e)
-        })?;
+    let _slot = queue::QueueSlot::acquire(&state)?;
+    rate_limit::throttle(&state).await;
+    connection_mode::ensure_link(&state, handler).await?;
+
+    let started = std::time::Instant::now();
+    let mut result = policy::write_with_policy(handler, char_uuid, service, &data, &policy).await;
+
+    // ? Replay the write once if it failed because the link dropped
+    if result.is_err() && *lock(&state.auto_reconnect_on_write_fail) && !handler.is_connected() {
+        info!("write_data: link lost, reconnecting before retrying write to {}", alias::characteristic_label(char_uuid));
+        lock(&state.session).record_write(false);
+        disconnect::record_disconnect(&state, disconnect::DisconnectReason::LinkLoss);
+        reconnect::reconnect_with_backoff(&state).await?;
+        state.auto_retry_writes.fetch_add(1, Ordering::SeqCst);
+        result = policy::write_with_policy(handler, char_uuid, service, &data, &policy).await;
+    }
+    {
+        let mut session = lock(&state.session);
+        session.record_write(result.is_ok());
+        session.record_latency(started.elapsed());
+        session.metrics.record_write(char_uuid, result.is_ok(), started.elapsed());
+        if result.is_err() {
+            session.metrics.record_error("write_data");
+        }
+    }
+    connection_mode::schedule_idle_disconnect(&state, handler);
+
+    result.map_err(report)?;
 
     // info!("write_data: Successfully wrote data {:?} to {:?}", data, char_uuid);
     Ok(format!("Successfully write data {:?} to {:?}.", data, service))
 }
 
 #[tauri::command]
-async fn receive_data(char_uuid: Uuid, service: Uuid) -> Result<Vec<u8>, String> {
-    info!("receive_data called - Characteristic: {}, Service: {}", char_uuid, service);
+async fn receive_data(state: tauri::State<'_, AppState>, char_uuid: Uuid, service: Uuid) -> Result<Vec<u8>, String> {
+    let started = std::time::Instant::now();
+    let result = read_characteristic(state.clone(), char_uuid, service).await;
+    events::log_operation(&state, "read", Some(char_uuid), result.as_deref().ok(), result.is_ok(), started);
+    result
+}
+
+async fn read_characteristic(state: tauri::State<'_, AppState>, char_uuid: Uuid, service: Uuid) -> Result<Vec<u8>, String> {
+    info!("receive_data called - Characteristic: {}, Service: {}", alias::characteristic_label(char_uuid), service);
+    ensure_connected(&state).await?;
     
-    let handler = tauri_plugin_blec::get_handler()
-        .map_err(|e| {
-            info!("receive_data: Get handle failed: {}", e);
-            format!("Get handle failed: {}", e)
-        })?;
+    let handler = state.handler()?;
 
-    let response = handler
+    connection_mode::ensure_link(&state, handler).await?;
+    let result = handler
         .recv_data(char_uuid, Some(service))
-        .await
-        .map_err(|e| {
-            info!("receive_data: Failed - Char: {}, Service: {}, Error: {}", char_uuid, service, e);
-            format!("Receive data from {:?} (Service: {:?}) failed: {}", char_uuid, service, e)
-        })?;
+        .await;
+    connection_mode::schedule_idle_disconnect(&state, handler);
+    {
+        let mut session = lock(&state.session);
+        session.record_read(result.is_ok());
+        if result.is_err() {
+            session.metrics.record_error("receive_data");
+        }
+    }
+
+    let response = result.map_err(|e| {
+        info!("receive_data: Failed - Char: {}, Service: {}, Error: {}", alias::characteristic_label(char_uuid), service, e);
+        String::from(report(BleError::ReadFailed(format!("Receive data from {} (Service: {:?}) failed: {}", alias::characteristic_label(char_uuid), service, e))))
+    })?;
 
-    info!("receive_data: Received data {:?} from {:?}", response, char_uuid);
+    info!("receive_data: Received data {:?} from {}", response, alias::characteristic_label(char_uuid));
     Ok(response)
 }
 
+/*
+    Set scan mode used by connect and preload_operation: "low_power", "balanced" or "high_duty".
+*/
+#[tauri::command]
+async fn set_scan_mode(state: tauri::State<'_, AppState>, mode: String) -> Result<String, BleError> {
+    let scan_mode = match mode.as_str() {
+        "low_power" => ScanMode::LowPower,
+        "balanced" => ScanMode::Balanced,
+        "high_duty" => ScanMode::HighDuty,
+        _ => return Err(BleError::InvalidArgument(format!("Unsupported scan mode: {}", mode))),
+    };
+    *lock(&state.scan_mode) = scan_mode;
+    Ok(format!("Scan mode set to: {}", mode))
+}
+
+/*
+    Claim the scanner until the guard is dropped.
+    Only one scan may run at a time, concurrent callers get ScanAlreadyInProgress.
+*/
+pub(crate) fn claim_scanner(state: &AppState) -> Result<FlagGuard<'_>, BleError> {
+    FlagGuard::acquire(&state.is_scanning).ok_or_else(|| report(BleError::ScanAlreadyInProgress))
+}
+
 /*
     Helper function to scan with channel for monitoring results.
-    This is used internally by preload_operation.
+    This is used internally by connect and preload_operation, callers must hold the claim_scanner guard.
 */
-async fn scan_with_monitor() -> Result<mpsc::Receiver<Vec<BleDevice>>, String> {
-    let handler = tauri_plugin_blec::get_handler()
-        .map_err(|e| format!("Get handle failed: {}", e))?;
+async fn scan_with_monitor(state: tauri::State<'_, AppState>) -> Result<mpsc::Receiver<Vec<BleDevice>>, String> {
+    let handler = state.handler()?;
 
     let (tx, rx) = mpsc::channel(10);
+    let period = lock(&state.scan_mode).discover_period();
 
     handler
-        .discover(Some(tx), MAXIUM_DISCOVER_PERIOD, ScanFilter::None, false)
+        .discover(Some(tx), period, ScanFilter::None, false)
         .await
         .map_err(|e| format!("Scan failed: {}", e))?;
     
@@ -390,13 +1441,22 @@ async fn scan_with_monitor() -> Result<mpsc::Receiver<Vec<BleDevice>>, String> {
     This function will monitor scan results and connect immediately when the target MAC address is discovered.
 */
 #[tauri::command]
-async fn preload_operation(state: tauri::State<'_, AppState>) -> Result<(), String> {
+#[tracing::instrument(skip_all)]
+async fn preload_operation(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    // ! Reject concurrent calls, e.g. a double click on "Connect"
+    let Some(_preload_guard) = FlagGuard::acquire(&state.preload_in_progress) else {
+        info!("preload_operation already running, ignoring duplicate call");
+        events::emit("ble://preload-duplicate-call", ());
+        return Err(BleError::PreloadAlreadyRunning.into());
+    };
+
     info!("=== Starting preload_operation ===");
     info!("Target device: {}", DEVICE_ADDRESS);
     
     // ? Start scanning with monitoring
     info!("Starting scan...");
-    let mut rx = scan_with_monitor().await?;
+    let scan_guard = claim_scanner(&state)?;
+    let mut rx = scan_with_monitor(state.clone()).await?;
     info!("Scan started, waiting for devices...");
     
     // ? Monitor scan results
@@ -415,22 +1475,26 @@ async fn preload_operation(state: tauri::State<'_, AppState>) -> Result<(), Stri
                 
                 // * Stop scanning immediately
                 info!("Stopping scan...");
-                let _ = stop_scan().await;
+                let _ = stop_scan(state.clone()).await;
+                drop(scan_guard);
                 info!("Scan stopped");
                 
                 // * Connect to the device
                 info!("Connecting to {}...", device.address);
-                match connect(state.clone(), &device.address).await {
+                match connect(app.clone(), state.clone(), &device.address).await {
                     Ok(_) => {
                         info!("Connected successfully!");
                         
                         // * Get current usable state from device (use last byte)
                         info!("Reading controller status...");
-                        match receive_data(CONTROLLER_USABLE_CHARACTERISTIC_UUID, SERVICE_UUID).await {
+                        match receive_data(state.clone(), CONTROLLER_USABLE_CHARACTERISTIC_UUID, service_uuid(&state)).await {
                             Ok(data) => {
-                                let usable = !data.is_empty() && *data.last().unwrap() == CONTROLLER_USABLE;
-                                set_controller_usable(state, usable).await?;
+                                let usable = data.last() == Some(&CONTROLLER_USABLE);
+                                set_controller_usable(state.clone(), usable).await?;
                                 info!("Controller usable: {}", usable);
+                                if usable {
+                                    script::run_on_ready_script(state.clone()).await?;
+                                }
                             }
                             Err(e) => {
                                 info!("Failed to get controller status: {}", e);
@@ -450,7 +1514,7 @@ async fn preload_operation(state: tauri::State<'_, AppState>) -> Result<(), Stri
     }
     
     info!("=== preload_operation: scan timeout, device not found ===");
-    Err(format!("Target device {} not found within scan period", DEVICE_ADDRESS))
+    Err(report(BleError::ScanTimeout(DEVICE_ADDRESS.to_string())).into())
 }
 
 
@@ -461,42 +1525,284 @@ fn check_ble_permissions() -> Result<bool, String> {
         .map_err(|e| format!("Permission check failed: {}", e))
 }
 
+/*
+    Center the joystick and disconnect before the app goes away.
+    Shared by the window CloseRequested and RunEvent::Exit handlers, only the first call runs.
+    ! A later call waits until the first run finished, so Exit cannot end the process halfway.
+    Does nothing if already disconnected.
+*/
+async fn shutdown_cleanup(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let _running = state.shutdown_lock.lock().await;
+    if state.is_shutting_down.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if !*lock(&state.is_connected) {
+        return;
+    }
+
+    info!("Shutting down, sending zero values before disconnect...");
+    let encoding = *lock(&state.axis_encoding);
+    for (uuid, axis) in [(X_CHARACTERISTIC_UUID, AXIS_X), (Y_CHARACTERISTIC_UUID, AXIS_Y), (R_CHARACTERISTIC_UUID, AXIS_R)] {
+        // ? write_data_as skips the pause_writes check, the safety zeroing must go out even while paused
+        let zero = encode_axis(JOYSTICK_ZERO_VALUE, &encoding[axis]);
+        if let Err(e) = write_data_as(state.clone(), uuid, service_uuid(&state), zero, policy::WritePolicy::reliable()).await {
+            info!("Error occurred when existing (zero value): {}", e);
+        }
+    }
+
+    // Perform disconnect logic directly without calling disconnect function
+    let handler = match state.handler() {
+        Ok(h) => h,
+        Err(e) => {
+            info!("Error occurred when existing (get handler): {}", e);
+            return;
+        }
+    };
+    reconnect::expect_disconnect(&state, true);
+    if let Err(e) = handler.disconnect().await {
+        info!("Error occurred when existing (disconnect): {}", e);
+    }
+    *lock(&state.is_connected) = false;
+    session::finish_session(app, &state);
+    state.ble_handler.invalidate();
+}
+
+/*
+    Wrap the command handler so every command is rejected with AppShuttingDown once shutdown_cleanup started,
+    instead of racing with the cleanup writes and disconnect.
+*/
+fn reject_during_shutdown<R: tauri::Runtime>(
+    commands: impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        if invoke.message.webview().state::<AppState>().is_shutting_down.load(Ordering::SeqCst) {
+            info!("Rejecting command {} during shutdown", invoke.message.command());
+            invoke.resolver.reject(BleError::AppShuttingDown);
+            return true;
+        }
+        commands(invoke)
+    }
+}
+
+/*
+    Catch copy-paste mistakes in the UUID tables at startup instead of connecting to the wrong characteristic.
+    Every UUID must be non-nil, characteristics must be distinct and share the service base prefix.
+*/
+pub(crate) fn check_uuids() -> Result<(), String> {
+    if SERVICE_UUID.is_nil() {
+        return Err("SERVICE_UUID is the nil UUID".to_string());
+    }
+    let prefix = &SERVICE_UUID.as_bytes()[..UUID_BASE_PREFIX_LEN];
+
+    for (i, (name, uuid)) in KNOWN_CHARACTERISTICS.iter().enumerate() {
+        if uuid.is_nil() {
+            return Err(format!("Characteristic {} has the nil UUID", name));
+        }
+        if &uuid.as_bytes()[..UUID_BASE_PREFIX_LEN] != prefix {
+            return Err(format!("Characteristic {} ({}) does not share the base prefix of SERVICE_UUID ({})", name, uuid, SERVICE_UUID));
+        }
+        if let Some((other, _)) = KNOWN_CHARACTERISTICS[..i].iter().find(|(_, other)| other == uuid) {
+            return Err(format!("Characteristics {} and {} share the UUID {}", other, name, uuid));
+        }
+    }
+    Ok(())
+}
+
+fn validate_uuids() {
+    if let Err(e) = check_uuids() {
+        panic!("{}", e);
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    validate_uuids();
+
+    let mut builder = tauri::Builder::default();
+    // ! Must be the first plugin, a second instance launched by a meguru:// link forwards it here and exits
+    #[cfg(desktop)]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.set_focus();
+            }
+        }));
+    }
+
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_blec::init())
         .manage(AppState::default())
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(reject_during_shutdown(tauri::generate_handler![
             set_connected_device_address,
             get_connected_device_address,
             get_controller_usable,
+            set_axis_encoding,
+            set_data_endianness,
+            set_service_uuid,
+            reset_service_uuid,
             poll_controller_status,
+            read_controller_status_raw,
             send_joystick_data,
             send_lifting_arm_value,
+            set_lifting_arm_range,
+            workspace::set_workspace_limits,
+            workspace::get_workspace_limits,
+            workspace::set_workspace_enforcement,
             send_arm_command,
+            emergency_stop,
+            get_arm_data,
+            format_arm_data,
+            set_default_write_type,
+            set_characteristic_write_type,
+            get_write_type,
+            pause_writes,
+            resume_writes,
+            set_scan_mode,
             preload_operation,
             check_ble_permissions,
             disconnect,
+            verify_device_identity,
             connect,
-        ])
+            script::run_script,
+            script::stop_script,
+            script::get_script_progress,
+            script::set_on_ready_script,
+            script::clear_on_ready_script,
+            sequence::send_arm_sequence,
+            sequence::cancel_arm_sequence,
+            sequence::send_arm_command_confirmed,
+            claw::activate_claw,
+            claw::deactivate_claw,
+            claw::set_claw_mode,
+            claw::get_claw_mode,
+            profile::save_device_profile,
+            profile::load_device_profile,
+            profile::list_device_profiles,
+            profile::export_device_profile,
+            profile::import_device_profile,
+            probe::probe_device,
+            position::move_home,
+            position::set_home_position,
+            position::get_home_position,
+            position::save_home_as_current,
+            position::transition_lifting_arm,
+            position::calibrate_arm_range,
+            session::get_session_history,
+            session::get_uptime_histogram,
+            logs::set_log_rotation,
+            logs::get_log_info,
+            session::reset_connection_stats,
+            metrics::get_metrics,
+            session::get_connection_stats,
+            subscription::subscribe_to_characteristic,
+            subscription::subscribe_to_characteristics,
+            subscription::unsubscribe_from_characteristic,
+            subscription::set_auto_resubscribe,
+            subscription::set_notification_threshold,
+            survey::scan_rssi_map,
+            survey::wait_for_device_discoverable,
+            survey::multi_scan,
+            survey::scan_all_meguru_devices,
+            survey::scan_and_list,
+            joystick::calculate_joystick_polar,
+            joystick::set_joystick_deadzone,
+            joystick::set_deadband_shape,
+            joystick::get_deadband_shape,
+            joystick::set_joystick_curve,
+            joystick::set_coalesce_joystick_writes,
+            joystick::get_joystick_curve,
+            battery::get_battery_level,
+            firmware::get_firmware_version,
+            firmware::check_feature_compatibility,
+            battery::set_low_battery_threshold,
+            joystick::move_relative,
+            joystick::get_relative_step_size,
+            joystick::set_relative_step_size,
+            joystick::move_left,
+            joystick::move_right,
+            joystick::move_up,
+            joystick::move_down,
+            joystick::move_in_direction,
+            joystick::set_joystick_target,
+            joystick::start_joystick_poll,
+            joystick::stop_joystick_poll,
+            joystick::set_r_axis_semantics,
+            joystick::get_r_axis_semantics,
+            joystick::send_joystick_normalized,
+            joystick::send_joystick_i16,
+            status::enable_controller_status_notification,
+            status::disable_controller_status_notification,
+            status::set_controller_status_strategy,
+            status::simulate_controller_not_usable,
+            adapter::get_ble_adapter_info,
+            alias::get_characteristic_by_alias,
+            alias::read_characteristic_by_alias,
+            alias::write_characteristic_by_alias,
+            batch::write_characteristics_atomic,
+            batch::write_characteristic_safe,
+            batch::write_and_verify,
+            alias::set_write_order,
+            connection_params::request_connection_parameters,
+            connection_mode::set_connection_mode,
+            connection_mode::get_connection_mode,
+            reconnect::set_auto_reconnect_on_write_fail,
+            reconnect::set_reconnect_strategy,
+            dev::enable_dev_mode,
+            dev::disable_dev_mode,
+            dev::send_raw_bytes,
+            dev::subscribe_all_debug,
+            dev::unsubscribe_all_debug,
+            disconnect::get_last_disconnect_reason,
+            quality::get_connection_quality,
+            quality::start_quality_monitor,
+            quality::stop_quality_monitor,
+            heartbeat::start_heartbeat,
+            heartbeat::stop_heartbeat,
+            heartbeat::set_heartbeat_characteristic,
+            rate_limit::set_write_rate_limit,
+            rate_limit::set_adaptive_rate_limit,
+            rate_limit::set_joystick_frame_rate,
+            rate_limit::set_emit_drop_events,
+            queue::get_queue_depth,
+            queue::get_pending_write_count,
+            queue::write_data_fire_and_forget,
+            queue::get_queue_stats,
+            queue::reset_queue_stats,
+            queue::set_queue_stats_capacity,
+            registry::list_known_devices,
+            registry::add_known_device,
+            registry::disconnect_and_forget,
+            commands::list_commands,
+            commands::describe_command,
+            events::get_last_error,
+            events::set_verbose_events,
+            tasks::list_active_tasks,
+            tasks::cancel_task,
+            events::clear_last_error,
+            selftest::run_self_test,
+        ]))
         .setup(|app| {
+            events::init(app.handle().clone());
+            queue::start_queue_sampler(app.handle().clone());
+            // ? The webview may not listen yet, it can still call run_self_test for the report
+            if let Err(e) = selftest::self_test(app.handle()) {
+                info!("Self test could not run: {}", e);
+            }
+            deeplink::init(app.handle());
             if let Some(window) = app.get_webview_window("main") {
-                window.on_window_event(|event| {
+                let app_handle = app.handle().clone();
+                window.on_window_event(move |event| {
                     if let tauri::WindowEvent::CloseRequested { .. } = event {
-                        tauri::async_runtime::spawn(async move {
-                            // Perform disconnect logic directly without calling disconnect function
-                            let handler = match tauri_plugin_blec::get_handler() {
-                                Ok(h) => h,
-                                Err(e) => {
-                                    info!("Error occurred when existing (get handler): {}", e);
-                                    return;
-                                }
-                            };
-                            if let Err(e) = handler.disconnect().await {
-                                info!("Error occurred when existing (disconnect): {}", e);
-                            }
+                        let app_handle = app_handle.clone();
+                        let task_handle = app_handle.clone();
+                        let handle = tauri::async_runtime::spawn(async move {
+                            shutdown_cleanup(&task_handle).await;
                         });
+                        tasks::track(&app_handle.state::<AppState>(), "shutdown_cleanup", &handle);
                     }
                 });
             } else {
@@ -504,6 +1810,142 @@ pub fn run() {
             }
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Window close is handled above, this also covers app.exit() and OS termination
+            if let tauri::RunEvent::Exit = event {
+                tauri::async_runtime::block_on(shutdown_cleanup(app_handle));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /*
+        Poll a future that completes without waiting, the tests have no async runtime.
+    */
+    fn poll_ready<F: std::future::Future>(future: F) -> Option<F::Output> {
+        let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+        match std::pin::pin!(future).poll(&mut context) {
+            std::task::Poll::Ready(output) => Some(output),
+            std::task::Poll::Pending => None,
+        }
+    }
+
+    #[test]
+    fn reset_for_testing_restores_defaults() {
+        let state = AppState::default();
+        *lock(&state.is_connected) = true;
+        *lock(&state.writes_paused) = true;
+        *lock(&state.data_endianness) = Endian::Big;
+        state.script_running.store(true, Ordering::SeqCst);
+        assert!(matches!(ensure_writes_allowed(&state), Err(BleError::WritesPaused)));
+
+        state.reset_for_testing();
+        assert!(!*lock(&state.is_connected));
+        assert!(ensure_writes_allowed(&state).is_ok());
+        assert_eq!(*lock(&state.data_endianness), Endian::Little);
+        assert!(!state.script_running.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn ensure_connected_fails_without_connection() {
+        let state = AppState::default();
+        assert!(matches!(poll_ready(ensure_connected(&state)), Some(Err(BleError::NotConnected))));
+
+        *lock(&state.is_connected) = true;
+        assert!(matches!(poll_ready(ensure_connected(&state)), Some(Ok(()))));
+    }
+
+    #[test]
+    fn normalize_mac_address_accepts_all_formats() {
+        for addr in ["3C:0F:02:D1:D3:8A", "3c:0f:02:d1:d3:8a", "3C-0F-02-D1-D3-8A", "3c0f02d1d38a", " 3c:0f:02:d1:d3:8a "] {
+            assert_eq!(normalize_mac_address(addr).ok().as_deref(), Some("3C:0F:02:D1:D3:8A"), "{}", addr);
+        }
+    }
+
+    #[test]
+    fn normalize_mac_address_rejects_wrong_length() {
+        for addr in ["", "3C:0F:02:D1:D3", "3C:0F:02:D1:D3:8A:00", "3c0f02d1d3", "3C:0F:02:D1:D38:A"] {
+            assert!(matches!(normalize_mac_address(addr), Err(BleError::InvalidArgument(_))), "{}", addr);
+        }
+    }
+
+    #[test]
+    fn normalize_mac_address_rejects_non_hex() {
+        for addr in ["3G:0F:02:D1:D3:8A", "zz0f02d1d38a", "3C:0F-02:D1:D3:8A"] {
+            assert!(matches!(normalize_mac_address(addr), Err(BleError::InvalidArgument(_))), "{}", addr);
+        }
+    }
+
+    #[test]
+    fn parse_received_bytes_little_endian() {
+        assert_eq!(parse_received_bytes(&[0x7F, 0x00], Endian::Little), 0x007F);
+        assert_eq!(parse_received_bytes(&[0x34, 0x12], Endian::Little), 0x1234);
+        assert_eq!(parse_received_bytes(&[0x34, 0x12, 0xFF], Endian::Little), 0x1234);
+    }
+
+    #[test]
+    fn parse_received_bytes_big_endian() {
+        assert_eq!(parse_received_bytes(&[0x00, 0x7F], Endian::Big), 0x007F);
+        assert_eq!(parse_received_bytes(&[0x12, 0x34], Endian::Big), 0x1234);
+        assert_eq!(parse_received_bytes(&[0x12, 0x34, 0xFF], Endian::Big), 0x1234);
+    }
+
+    #[test]
+    fn arm_data_accepts_byte_range() {
+        assert!(ArmData::new(0x00, 0x7F, 0xFF, true).is_ok());
+        assert!(ArmData::new(0xFF, 0xFF, 0xFF, false).is_ok());
+    }
+
+    #[test]
+    fn arm_data_rejects_values_above_byte_range() {
+        assert!(matches!(ArmData::new(0x100, 0x7F, 0x7F, true), Err(BleError::InvalidPayload(_))));
+        assert!(matches!(ArmData::new(0x7F, 0x100, 0x7F, true), Err(BleError::InvalidPayload(_))));
+        assert!(matches!(ArmData::new(0x7F, 0x7F, 0x100, true), Err(BleError::InvalidPayload(_))));
+    }
+
+    #[test]
+    fn decode_axis_accepts_valid_buffers() {
+        assert_eq!(decode_axis(&[0x7F, 0x00], &AxisEncoding { width: 2, endian: Endian::Little }).ok(), Some(0x7F));
+        assert_eq!(decode_axis(&[0x00, 0x7F], &AxisEncoding { width: 2, endian: Endian::Big }).ok(), Some(0x7F));
+        assert_eq!(decode_axis(&[0x7F], &AxisEncoding { width: 1, endian: Endian::Little }).ok(), Some(0x7F));
+    }
+
+    #[test]
+    fn decode_axis_rejects_empty_buffer() {
+        assert!(matches!(decode_axis(&[], &AxisEncoding::default()), Err(BleError::InvalidPayload(_))));
+    }
+
+    #[test]
+    fn decode_axis_rejects_oversized_buffer() {
+        assert!(matches!(decode_axis(&[0x7F, 0x00, 0x00], &AxisEncoding::default()), Err(BleError::InvalidPayload(_))));
+    }
+
+    #[test]
+    fn decode_axis_reverses_encode_axis() {
+        for width in 1..=MAXIUM_AXIS_WIDTH {
+            for endian in [Endian::Little, Endian::Big] {
+                let enc = AxisEncoding { width, endian };
+                assert_eq!(decode_axis(&encode_axis(0xA5, &enc), &enc).ok(), Some(0xA5));
+            }
+        }
+    }
+
+    #[test]
+    fn decode_axis_rejects_values_wider_than_u16() {
+        let enc = AxisEncoding { width: 4, endian: Endian::Little };
+        assert!(matches!(decode_axis(&[0x00, 0x00, 0x01, 0x00], &enc), Err(BleError::InvalidPayload(_))));
+    }
+
+    #[test]
+    fn parse_received_bytes_short_data() {
+        for endian in [Endian::Little, Endian::Big] {
+            assert_eq!(parse_received_bytes(&[], endian), 0);
+            assert_eq!(parse_received_bytes(&[0x7F], endian), 0x7F);
+        }
+    }
 }
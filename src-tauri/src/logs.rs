@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use serde::Serialize;
+use tauri::Manager;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::session::unix_millis;
+use crate::{lock, AppState};
+
+pub const DEFAULT_MAX_LOG_FILES: usize = 7;
+pub const DEFAULT_MAX_LOG_SIZE_BYTES: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogInfo {
+    pub path: String,
+    pub size_bytes: u64,
+    pub file_count: u8,
+}
+
+pub(crate) fn log_dir(app: &tauri::AppHandle) -> Result<PathBuf, BleError> {
+    let dir = app
+        .path()
+        .app_log_dir()
+        .map_err(|e| BleError::Io(format!("App log dir unavailable: {}", e)))?;
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn log_files(dir: &Path) -> Result<Vec<(PathBuf, SystemTime, u64)>, BleError> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            files.push((entry.path(), metadata.modified()?, metadata.len()));
+        }
+    }
+    Ok(files)
+}
+
+/*
+    Delete all but the newest `max_log_files` files in $APP_LOG_DIR.
+*/
+fn remove_old_logs(dir: &Path, max_log_files: usize) -> Result<(), BleError> {
+    let mut files = log_files(dir)?;
+    files.sort_by_key(|(_, modified, _)| std::cmp::Reverse(*modified));
+    for (path, _, _) in files.into_iter().skip(max_log_files) {
+        info!("Removing old log file {}", path.display());
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/*
+    Move `path` aside once it reaches the configured size, then clean up old files in the background.
+    Called before appending to a log file.
+*/
+pub(crate) fn rotate_if_needed(state: &AppState, path: &Path) {
+    let max_size = *lock(&state.max_log_size_bytes);
+    let size = std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0);
+    if size < max_size {
+        return;
+    }
+
+    let (Some(dir), Some(stem), Some(extension)) = (path.parent(), path.file_stem(), path.extension()) else {
+        return;
+    };
+    let rotated = dir.join(format!(
+        "{}.{}.{}",
+        stem.to_string_lossy(),
+        unix_millis(SystemTime::now()),
+        extension.to_string_lossy()
+    ));
+    if let Err(e) = std::fs::rename(path, &rotated) {
+        info!("Log rotation of {} failed: {}", path.display(), e);
+        return;
+    }
+    info!("Rotated {} to {}", path.display(), rotated.display());
+
+    let dir = dir.to_path_buf();
+    let max_log_files = *lock(&state.max_log_files);
+    let handle = tauri::async_runtime::spawn(async move {
+        if let Err(e) = remove_old_logs(&dir, max_log_files) {
+            info!("Log cleanup failed: {}", e);
+        }
+    });
+    crate::tasks::track(state, "log_cleanup", &handle);
+}
+
+#[tauri::command]
+pub async fn set_log_rotation(state: tauri::State<'_, AppState>, max_log_files: usize, max_log_size_bytes: u64) -> Result<String, String> {
+    if max_log_files == 0 || max_log_size_bytes == 0 {
+        return Err("Log file count and size must be greater than 0".to_string());
+    }
+    *lock(&state.max_log_files) = max_log_files;
+    *lock(&state.max_log_size_bytes) = max_log_size_bytes;
+    Ok(format!("Log rotation set to: {} files of {} bytes", max_log_files, max_log_size_bytes))
+}
+
+#[tauri::command]
+pub async fn get_log_info(app: tauri::AppHandle) -> Result<LogInfo, BleError> {
+    let dir = log_dir(&app)?;
+    let files = log_files(&dir)?;
+    Ok(LogInfo {
+        path: dir.to_string_lossy().into_owned(),
+        size_bytes: files.iter().map(|(_, _, size)| size).sum(),
+        file_count: u8::try_from(files.len()).unwrap_or(u8::MAX),
+    })
+}
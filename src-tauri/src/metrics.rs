@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::alias::characteristic_label;
+use crate::{lock, AppState};
+
+#[derive(Debug, Default)]
+struct WriteMetrics {
+    total: u64,
+    errors: u64,
+    duration_ms_sum: f64,
+}
+
+/*
+    Per characteristic write and per command error counters, kept in the session tracker
+    so they reset together with the connection stats.
+*/
+#[derive(Debug, Default)]
+pub struct Metrics {
+    writes: BTreeMap<String, WriteMetrics>,
+    errors: BTreeMap<&'static str, u64>,
+}
+
+impl Metrics {
+    pub fn record_write(&mut self, char_uuid: Uuid, success: bool, duration: Duration) {
+        let entry = self.writes.entry(characteristic_label(char_uuid)).or_default();
+        entry.total += 1;
+        if !success {
+            entry.errors += 1;
+        }
+        entry.duration_ms_sum += duration.as_secs_f64() * 1000.0;
+    }
+
+    pub fn record_error(&mut self, command: &'static str) {
+        *self.errors.entry(command).or_default() += 1;
+    }
+
+    /*
+        Prometheus text exposition format.
+    */
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# HELP ble_writes_total Writes per characteristic.");
+        let _ = writeln!(out, "# TYPE ble_writes_total counter");
+        for (label, write) in &self.writes {
+            let _ = writeln!(out, "ble_writes_total{{characteristic=\"{}\"}} {}", label, write.total);
+        }
+
+        let _ = writeln!(out, "# HELP ble_write_errors_total Failed writes per characteristic.");
+        let _ = writeln!(out, "# TYPE ble_write_errors_total counter");
+        for (label, write) in &self.writes {
+            let _ = writeln!(out, "ble_write_errors_total{{characteristic=\"{}\"}} {}", label, write.errors);
+        }
+
+        let _ = writeln!(out, "# HELP ble_write_duration_ms Write duration per characteristic in milliseconds.");
+        let _ = writeln!(out, "# TYPE ble_write_duration_ms summary");
+        for (label, write) in &self.writes {
+            let _ = writeln!(out, "ble_write_duration_ms_sum{{characteristic=\"{}\"}} {:.3}", label, write.duration_ms_sum);
+            let _ = writeln!(out, "ble_write_duration_ms_count{{characteristic=\"{}\"}} {}", label, write.total);
+        }
+
+        let _ = writeln!(out, "# HELP ble_errors_total Failed calls per command.");
+        let _ = writeln!(out, "# TYPE ble_errors_total counter");
+        for (command, count) in &self.errors {
+            let _ = writeln!(out, "ble_errors_total{{command=\"{}\"}} {}", command, count);
+        }
+
+        out
+    }
+}
+
+pub(crate) fn record_error(state: &AppState, command: &'static str) {
+    lock(&state.session).metrics.record_error(command);
+}
+
+/*
+    Metrics of the current stats epoch in Prometheus text format, reset by reset_connection_stats.
+*/
+#[tauri::command]
+pub async fn get_metrics(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    Ok(lock(&state.session).metrics.render())
+}
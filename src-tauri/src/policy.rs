@@ -0,0 +1,83 @@
+use std::time::Duration;
+use tauri_plugin_blec::{Handler, models::WriteType};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::alias::characteristic_label;
+use crate::error::BleError;
+
+// Timeout of a single write unless a policy says otherwise
+pub const DEFAULT_WRITE_TIMEOUT_MS: u64 = 1000;
+
+/*
+    Timeout, retries and write type of a characteristic write.
+*/
+#[derive(Debug)]
+pub struct WritePolicy {
+    pub timeout: Duration,
+    pub max_retries: u8,
+    pub retry_delay: Duration,
+    pub write_type: WriteType,
+}
+
+impl WritePolicy {
+    /*
+        One attempt with the given write type, used by write_data.
+    */
+    pub fn once(write_type: WriteType) -> Self {
+        Self {
+            timeout: Duration::from_millis(DEFAULT_WRITE_TIMEOUT_MS),
+            max_retries: 0,
+            retry_delay: Duration::ZERO,
+            write_type,
+        }
+    }
+
+    /*
+        Fast unacknowledged write for data that is replaced soon anyway, e.g. joystick frames.
+    */
+    pub fn best_effort() -> Self {
+        Self {
+            timeout: Duration::from_millis(250),
+            ..Self::once(WriteType::WithoutResponse)
+        }
+    }
+
+    /*
+        Acknowledged write retried up to 3 times, e.g. for stops and configuration.
+    */
+    pub fn reliable() -> Self {
+        Self {
+            max_retries: 3,
+            retry_delay: Duration::from_millis(100),
+            ..Self::once(WriteType::WithResponse)
+        }
+    }
+
+    fn write_type(&self) -> WriteType {
+        match self.write_type {
+            WriteType::WithResponse => WriteType::WithResponse,
+            WriteType::WithoutResponse => WriteType::WithoutResponse,
+        }
+    }
+}
+
+/*
+    Write `data` following `policy`: every attempt is bounded by the timeout, failed attempts are retried
+    after the retry delay. The error describes the last failed attempt.
+*/
+pub(crate) async fn write_with_policy(handler: &Handler, char_uuid: Uuid, service: Uuid, data: &[u8], policy: &WritePolicy) -> Result<(), BleError> {
+    let mut last_error = String::new();
+    for attempt in 0..=policy.max_retries {
+        if attempt > 0 {
+            info!("Retrying write to {} ({}/{})", characteristic_label(char_uuid), attempt, policy.max_retries);
+            tokio::time::sleep(policy.retry_delay).await;
+        }
+        match tokio::time::timeout(policy.timeout, handler.send_data(char_uuid, Some(service), data, policy.write_type())).await {
+            Ok(Ok(())) => return Ok(()),
+            Ok(Err(e)) => last_error = e.to_string(),
+            Err(_) => last_error = format!("timed out after {} ms", policy.timeout.as_millis()),
+        }
+    }
+    Err(BleError::WriteFailed(format!("Send {:?} to {} (Service: {:?}) failed: {}", data, characteristic_label(char_uuid), service, last_error)))
+}
@@ -0,0 +1,128 @@
+use std::time::Duration;
+use serde::Serialize;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::{ensure_connected, lock, service_uuid, AppState, ArmPosition, FlagGuard, AXIS_ALIASES, JOYSTICK_ZERO_VALUE, PRIMARY_LIFTING_ARM_CHANNEL};
+
+// Interval between interpolated writes of transition_lifting_arm
+const TRANSITION_STEP_MS: u64 = 20;
+
+/*
+    Return all axes to the configured home position.
+    Joystick axes are sent first, then the primary lifting arm channel.
+*/
+#[tauri::command]
+pub async fn move_home(state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    let home = *lock(&state.home_position);
+    info!("Moving to home position: {:?}", home);
+
+    crate::send_joystick_data(state.clone(), home.x, home.y, home.r).await?;
+    crate::send_lifting_arm_value(state, PRIMARY_LIFTING_ARM_CHANNEL.to_string(), home.lifting_arm).await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_home_position(state: tauri::State<'_, AppState>, x: u8, y: u8, r: u8, lifting_arm: u8) -> Result<String, String> {
+    let home = ArmPosition { x, y, r, lifting_arm };
+    *lock(&state.home_position) = home;
+    Ok(format!("Home position set to {:?}", home))
+}
+
+#[tauri::command]
+pub async fn get_home_position(state: tauri::State<'_, AppState>) -> Result<ArmPosition, String> {
+    Ok(*lock(&state.home_position))
+}
+
+/*
+    Store the last arm state sent to the device as the new home position.
+*/
+#[tauri::command]
+pub async fn save_home_as_current(state: tauri::State<'_, AppState>) -> Result<ArmPosition, String> {
+    let current = *lock(&state.last_arm_state);
+    *lock(&state.home_position) = current;
+    Ok(current)
+}
+
+/*
+    Move the primary lifting arm from `from` to `to` in linearly interpolated steps,
+    one write every TRANSITION_STEP_MS spread over `duration_ms`.
+    `from` is taken as given, so this also works when the actual position is unknown.
+*/
+#[tauri::command]
+pub async fn transition_lifting_arm(state: tauri::State<'_, AppState>, from: u8, to: u8, duration_ms: u64) -> Result<(), BleError> {
+    let _active = FlagGuard::acquire(&state.lifting_arm_transition_active).ok_or(BleError::TransitionAlreadyRunning)?;
+
+    let steps = (duration_ms / TRANSITION_STEP_MS).max(1);
+    info!("Lifting arm transition {} -> {} in {} steps", from, to, steps);
+
+    for step in 1..=steps {
+        let value = from as i64 + (to as i64 - from as i64) * step as i64 / steps as i64;
+        crate::send_lifting_arm_value(state.clone(), PRIMARY_LIFTING_ARM_CHANNEL.to_string(), value as u8).await?;
+        if step < steps {
+            tokio::time::sleep(Duration::from_millis(TRANSITION_STEP_MS)).await;
+        }
+    }
+    Ok(())
+}
+
+/*
+    Positions an axis actually reaches when driven to 0x00 and 0xFF.
+*/
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ArmRangeCalibration {
+    pub min_byte: u8,
+    pub max_byte: u8,
+    pub center_byte: u8,
+}
+
+/*
+    Axis index of "x", "y", "r" or their characteristic aliases "x_axis", "y_axis", "r_axis".
+*/
+fn parse_axis(axis: &str) -> Result<usize, BleError> {
+    let axis = axis.to_lowercase();
+    AXIS_ALIASES
+        .iter()
+        .position(|alias| *alias == axis || alias.strip_suffix("_axis") == Some(axis.as_str()))
+        .ok_or_else(|| BleError::InvalidArgument(format!("Unsupported axis: {}", axis)))
+}
+
+async fn drive_and_read(state: &tauri::State<'_, AppState>, axis: usize, value: u8, settle_ms: u64) -> Result<u8, BleError> {
+    let char_uuid = crate::alias::characteristic_by_alias(state, AXIS_ALIASES[axis])?;
+    let encoding = lock(&state.axis_encoding)[axis];
+    crate::write_data(state.clone(), char_uuid, service_uuid(state), crate::encode_axis(value, &encoding)).await?;
+    tokio::time::sleep(Duration::from_millis(settle_ms)).await;
+
+    let data = crate::receive_data(state.clone(), char_uuid, service_uuid(state)).await?;
+    let position = crate::decode_axis(&data, &encoding)?;
+    Ok(position.min(u8::MAX as u16) as u8)
+}
+
+/*
+    Drive `axis` to 0x00 and to 0xFF, waiting `duration_per_direction_ms` at each end, and read back
+    where it actually stopped. The axis is returned to center afterwards. Requires readable axis characteristics.
+*/
+#[tauri::command]
+pub async fn calibrate_arm_range(state: tauri::State<'_, AppState>, axis: String, duration_per_direction_ms: u64) -> Result<ArmRangeCalibration, BleError> {
+    ensure_connected(&state).await?;
+    let index = parse_axis(&axis)?;
+
+    info!("Calibrating {} range", AXIS_ALIASES[index]);
+    let measured = async {
+        let min_byte = drive_and_read(&state, index, 0x00, duration_per_direction_ms).await?;
+        let max_byte = drive_and_read(&state, index, u8::MAX, duration_per_direction_ms).await?;
+        Ok::<_, BleError>((min_byte, max_byte))
+    }
+    .await;
+    drive_and_read(&state, index, JOYSTICK_ZERO_VALUE, 0).await?;
+
+    let (min_byte, max_byte) = measured?;
+    let calibration = ArmRangeCalibration {
+        min_byte,
+        max_byte,
+        center_byte: ((min_byte as u16 + max_byte as u16) / 2) as u8,
+    };
+    info!("{} range: {:?}", AXIS_ALIASES[index], calibration);
+    lock(&state.axis_calibration)[index] = Some(calibration);
+    Ok(calibration)
+}
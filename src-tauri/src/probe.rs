@@ -0,0 +1,70 @@
+use serde::Serialize;
+use tauri::Emitter;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::error::BleError;
+use crate::{lock, service_uuid, AppState};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeResult {
+    pub matched: Vec<(String, Uuid)>,
+    pub unmatched_device: Vec<Uuid>,
+    pub missing_expected: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct MissingCharacteristic {
+    name: String,
+    uuid: String,
+}
+
+/*
+    Enumerate services of the connected device and compare its characteristics with the known constants.
+    Emits ble://missing-characteristic for every expected characteristic the firmware does not expose.
+*/
+#[tauri::command]
+pub async fn probe_device(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<ProbeResult, BleError> {
+    let address = lock(&state.connected_address).clone().ok_or(BleError::NotConnected)?;
+
+    let handler = state.handler()?;
+
+    let services = handler
+        .discover_services(&address)
+        .await
+        .map_err(|e| BleError::ProbeFailed(format!("Discover services of {} failed: {}", address, e)))?;
+
+    let discovered: Vec<Uuid> = services
+        .iter()
+        .filter(|service| service.uuid == service_uuid(&state))
+        .flat_map(|service| service.characteristics.iter().map(|c| c.uuid))
+        .collect();
+    info!("probe_device: {} characteristic(s) found in service {}", discovered.len(), service_uuid(&state));
+
+    let mut result = ProbeResult {
+        matched: Vec::new(),
+        unmatched_device: Vec::new(),
+        missing_expected: Vec::new(),
+    };
+
+    let known = crate::alias::known_characteristics(&state);
+    for (name, uuid) in known.iter() {
+        if discovered.contains(uuid) {
+            result.matched.push((name.to_string(), *uuid));
+        } else {
+            info!("probe_device: expected characteristic {} ({}) missing", name, uuid);
+            result.missing_expected.push(name.to_string());
+            let _ = app.emit("ble://missing-characteristic", MissingCharacteristic {
+                name: name.to_string(),
+                uuid: uuid.to_string(),
+            });
+        }
+    }
+
+    result.unmatched_device = discovered
+        .into_iter()
+        .filter(|uuid| !known.iter().any(|(_, known)| known == uuid))
+        .collect();
+
+    Ok(result)
+}
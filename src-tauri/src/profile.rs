@@ -0,0 +1,440 @@
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::connection_params::ConnectionParams;
+use crate::error::BleError;
+use crate::joystick::RAxisSemantics;
+use crate::rate_limit::{MAX_JOYSTICK_FRAME_RATE, MAX_WRITE_RATE};
+use crate::{lock, AppState, AxisEncoding, Endian, ScanMode, KNOWN_CHARACTERISTICS, MAXIUM_AXIS_WIDTH};
+
+// Bump when a field changes meaning and append a step to MIGRATIONS, profiles with a newer version are rejected
+const CURRENT_VERSION: u32 = 2;
+// Version of profiles saved before the version field existed
+const UNVERSIONED_PROFILE: u32 = 0;
+
+/*
+    Migration steps, MIGRATIONS[n] upgrades the JSON of a version n profile to version n + 1.
+*/
+const MIGRATIONS: [fn(&mut serde_json::Value); CURRENT_VERSION as usize] = [migrate_v0_to_v1, migrate_v1_to_v2];
+
+fn default_profile_version() -> u32 {
+    CURRENT_VERSION
+}
+
+/*
+    Snapshot of the tunable device settings, stored as
+    $APP_DATA_DIR/profiles/{profile_name}.json or shared through export_device_profile.
+    Settings missing in older profiles are left unchanged when applied.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceProfile {
+    #[serde(default = "default_profile_version")]
+    pub version: u32,
+    pub name: String,
+    pub service_uuid: Uuid,
+    // ? The other characteristic UUIDs are compiled in, only the heartbeat characteristic is configurable
+    #[serde(default)]
+    pub heartbeat_characteristic: Option<Uuid>,
+    pub axis_encoding: [AxisEncoding; 3],
+    #[serde(default)]
+    pub data_endianness: Option<Endian>,
+    #[serde(default)]
+    pub joystick_deadzone: Option<f32>,
+    #[serde(default)]
+    pub r_axis_semantics: Option<RAxisSemantics>,
+    #[serde(default)]
+    pub controller_status_poll_interval_ms: Option<u64>,
+    #[serde(default)]
+    pub scan_mode: Option<ScanMode>,
+    #[serde(default)]
+    pub write_rate_limit: Option<u32>,
+    #[serde(default)]
+    pub adaptive_rate_limit: Option<bool>,
+    #[serde(default)]
+    pub joystick_frame_rate: Option<u32>,
+    #[serde(default)]
+    pub disconnect_after_ms: Option<u64>,
+    #[serde(default)]
+    pub claw_hold_duration_ms: Option<u64>,
+    #[serde(default)]
+    pub connection_params: Option<ConnectionParams>,
+    // Device connected when the profile was saved
+    #[serde(default)]
+    pub device_address: Option<String>,
+}
+
+impl DeviceProfile {
+    fn capture(name: &str, state: &AppState) -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            name: name.to_string(),
+            service_uuid: crate::service_uuid(state),
+            heartbeat_characteristic: Some(*lock(&state.heartbeat_characteristic)),
+            axis_encoding: *lock(&state.axis_encoding),
+            data_endianness: Some(*lock(&state.data_endianness)),
+            joystick_deadzone: Some(*lock(&state.joystick_deadzone)),
+            r_axis_semantics: Some(*lock(&state.r_axis_semantics)),
+            controller_status_poll_interval_ms: Some(*lock(&state.controller_status_poll_interval_ms)),
+            scan_mode: Some(*lock(&state.scan_mode)),
+            write_rate_limit: Some(lock(&state.rate_limiter).writes_per_second),
+            adaptive_rate_limit: Some(*lock(&state.adaptive_rate_limit)),
+            joystick_frame_rate: Some(lock(&state.frame_throttle).frames_per_second),
+            disconnect_after_ms: Some(*lock(&state.disconnect_after_ms)),
+            claw_hold_duration_ms: Some(*lock(&state.claw_hold_duration_ms)),
+            connection_params: *lock(&state.connection_params),
+            device_address: lock(&state.connected_address).clone(),
+        }
+    }
+
+    /*
+        Reject profiles of a newer app version, nil or clashing UUIDs and out of range settings.
+    */
+    fn validate(&self) -> Result<(), BleError> {
+        if self.version > CURRENT_VERSION {
+            return Err(BleError::InvalidArgument(format!("Profile version {} is newer than supported version {}", self.version, CURRENT_VERSION)));
+        }
+        if self.service_uuid.is_nil() {
+            return Err(BleError::InvalidUuid("Service UUID is the nil UUID".to_string()));
+        }
+        if let Some(heartbeat) = self.heartbeat_characteristic {
+            if heartbeat.is_nil() {
+                return Err(BleError::InvalidUuid("Heartbeat characteristic is the nil UUID".to_string()));
+            }
+            // The other characteristics are compiled in, the heartbeat must not shadow one of them or the service
+            let clash = KNOWN_CHARACTERISTICS.iter().find(|(name, uuid)| *name != "heartbeat" && *uuid == heartbeat);
+            if let Some((name, _)) = clash {
+                return Err(BleError::InvalidUuid(format!("Heartbeat characteristic {} is the {} characteristic", heartbeat, name)));
+            }
+            if heartbeat == self.service_uuid {
+                return Err(BleError::InvalidUuid(format!("Heartbeat characteristic {} is the service UUID", heartbeat)));
+            }
+        }
+        if let Some(encoding) = self.axis_encoding.iter().find(|encoding| encoding.width == 0 || encoding.width > MAXIUM_AXIS_WIDTH) {
+            return Err(BleError::InvalidArgument(format!("Unsupported axis data width: {}", encoding.width)));
+        }
+        if self.joystick_deadzone.is_some_and(|deadzone| !(0.0..=1.0).contains(&deadzone)) {
+            return Err(BleError::InvalidArgument("Deadzone must be between 0.0 and 1.0".to_string()));
+        }
+        if self.controller_status_poll_interval_ms == Some(0) {
+            return Err(BleError::InvalidArgument("Poll interval must be greater than 0".to_string()));
+        }
+        if self.write_rate_limit.is_some_and(|rate| rate > MAX_WRITE_RATE) {
+            return Err(BleError::InvalidArgument(format!("Write rate limit must be at most {} writes/s", MAX_WRITE_RATE)));
+        }
+        if self.joystick_frame_rate.is_some_and(|rate| rate > MAX_JOYSTICK_FRAME_RATE) {
+            return Err(BleError::InvalidArgument(format!("Joystick frame rate must be at most {} frames/s", MAX_JOYSTICK_FRAME_RATE)));
+        }
+        if let Some(delay_ms) = self.disconnect_after_ms {
+            crate::connection_mode::check_disconnect_after(delay_ms)?;
+        }
+        if let Some(params) = &self.connection_params {
+            params.validate()?;
+        }
+        Ok(())
+    }
+
+    fn apply(&self, app: &tauri::AppHandle, state: &AppState) {
+        *lock(&state.axis_encoding) = self.axis_encoding;
+        *lock(&state.active_service_uuid) = self.service_uuid;
+        if let Some(uuid) = self.heartbeat_characteristic {
+            *lock(&state.heartbeat_characteristic) = uuid;
+        }
+        if let Some(endian) = self.data_endianness {
+            *lock(&state.data_endianness) = endian;
+        }
+        if let Some(deadzone) = self.joystick_deadzone {
+            *lock(&state.joystick_deadzone) = deadzone;
+        }
+        if let Some(semantics) = self.r_axis_semantics {
+            *lock(&state.r_axis_semantics) = semantics;
+        }
+        if let Some(interval_ms) = self.controller_status_poll_interval_ms {
+            *lock(&state.controller_status_poll_interval_ms) = interval_ms;
+        }
+        if let Some(mode) = self.scan_mode {
+            *lock(&state.scan_mode) = mode;
+        }
+        if let Some(enabled) = self.adaptive_rate_limit {
+            *lock(&state.adaptive_rate_limit) = enabled;
+        }
+        if let Some(writes_per_second) = self.write_rate_limit {
+            lock(&state.rate_limiter).set_rate(writes_per_second);
+            let rssi = lock(&state.session).last_rssi;
+            crate::rate_limit::adapt_to_rssi(state, rssi);
+        }
+        if let Some(frames_per_second) = self.joystick_frame_rate {
+            crate::rate_limit::set_frame_rate(app.clone(), state, frames_per_second);
+        }
+        if let Some(delay_ms) = self.disconnect_after_ms {
+            *lock(&state.disconnect_after_ms) = delay_ms;
+        }
+        if let Some(hold_ms) = self.claw_hold_duration_ms {
+            *lock(&state.claw_hold_duration_ms) = hold_ms;
+        }
+        if let Some(params) = self.connection_params {
+            // Requested on the next connect
+            *lock(&state.connection_params) = Some(params);
+        }
+    }
+}
+
+// 0 -> 1: only the version field was added
+fn migrate_v0_to_v1(_json: &mut serde_json::Value) {}
+
+// 1 -> 2: the never applied characteristics list is dropped, its heartbeat entry becomes heartbeat_characteristic
+fn migrate_v1_to_v2(json: &mut serde_json::Value) {
+    let Some(profile) = json.as_object_mut() else {
+        return;
+    };
+    let Some(characteristics) = profile.remove("characteristics") else {
+        return;
+    };
+    let heartbeat = characteristics.as_array().and_then(|entries| {
+        entries
+            .iter()
+            .filter_map(|entry| entry.as_array())
+            .find(|entry| entry.first().and_then(|alias| alias.as_str()) == Some("heartbeat"))
+            .and_then(|entry| entry.get(1).cloned())
+    });
+    if let Some(uuid) = heartbeat {
+        profile.insert("heartbeat_characteristic".to_string(), uuid);
+    }
+}
+
+/*
+    Upgrade the JSON of a profile saved as `from_version` by running every step up to CURRENT_VERSION, then parse it.
+    Profiles of a newer app version are rejected, their fields cannot be interpreted.
+*/
+fn migrate_profile(mut json: serde_json::Value, from_version: u32) -> Result<DeviceProfile, String> {
+    if from_version > CURRENT_VERSION {
+        return Err(format!("Profile version {} is newer than supported version {}", from_version, CURRENT_VERSION));
+    }
+    for (version, step) in MIGRATIONS.iter().enumerate().skip(from_version as usize) {
+        step(&mut json);
+        info!("Migrated profile from version {} to {}", version, version + 1);
+    }
+    if let Some(profile) = json.as_object_mut() {
+        profile.insert("version".to_string(), CURRENT_VERSION.into());
+    }
+    serde_json::from_value(json).map_err(|e| e.to_string())
+}
+
+/*
+    Parse a stored or imported profile, migrating profiles of older versions and rejecting newer ones.
+*/
+fn parse_profile(content: &str) -> Result<DeviceProfile, String> {
+    let json: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let version = json
+        .get("version")
+        .and_then(|version| version.as_u64())
+        .map_or(UNVERSIONED_PROFILE, |version| u32::try_from(version).unwrap_or(u32::MAX));
+    migrate_profile(json, version)
+}
+
+fn profiles_dir(app: &tauri::AppHandle) -> Result<PathBuf, BleError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| BleError::Io(format!("App data dir unavailable: {}", e)))?
+        .join("profiles");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn profile_path(app: &tauri::AppHandle, profile_name: &str) -> Result<PathBuf, BleError> {
+    if profile_name.is_empty() || profile_name.contains(['/', '\\', '.']) {
+        return Err(BleError::InvalidProfileName(profile_name.to_string()));
+    }
+    Ok(profiles_dir(app)?.join(format!("{}.json", profile_name)))
+}
+
+/*
+    Delete all saved profiles captured while connected to `address`.
+*/
+pub(crate) fn delete_profiles_for_device(app: &tauri::AppHandle, address: &str) -> Result<usize, BleError> {
+    let mut deleted = 0;
+    for entry in std::fs::read_dir(profiles_dir(app)?)? {
+        let path = entry?.path();
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(profile) = parse_profile(&content) else {
+            continue;
+        };
+        if profile.device_address.is_some_and(|addr| addr.eq_ignore_ascii_case(address)) {
+            std::fs::remove_file(&path)?;
+            info!("Deleted profile {} of device {}", profile.name, address);
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
+
+/*
+    Parse every saved profile, returning how many were checked.
+    Fails on the first profile that cannot be read or parsed.
+*/
+pub(crate) fn check_profiles(app: &tauri::AppHandle) -> Result<usize, BleError> {
+    let mut checked = 0;
+    for entry in std::fs::read_dir(profiles_dir(app)?)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let content = std::fs::read_to_string(&path)?;
+        parse_profile(&content)
+            .map_err(|e| BleError::Io(format!("Parse profile {:?} failed: {}", path, e)))?;
+        checked += 1;
+    }
+    Ok(checked)
+}
+
+#[tauri::command]
+pub async fn save_device_profile(app: tauri::AppHandle, state: tauri::State<'_, AppState>, profile_name: String) -> Result<(), BleError> {
+    let path = profile_path(&app, &profile_name)?;
+    let profile = DeviceProfile::capture(&profile_name, &state);
+    let json = serde_json::to_string_pretty(&profile)
+        .map_err(|e| BleError::Io(format!("Serialize profile failed: {}", e)))?;
+    std::fs::write(&path, json)?;
+    info!("Device profile {} saved to {:?}", profile_name, path);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn load_device_profile(app: tauri::AppHandle, state: tauri::State<'_, AppState>, profile_name: String) -> Result<(), BleError> {
+    let path = profile_path(&app, &profile_name)?;
+    let content = std::fs::read_to_string(&path)?;
+    let profile = parse_profile(&content)
+        .map_err(|e| BleError::Io(format!("Parse profile {} failed: {}", profile_name, e)))?;
+    profile.validate()?;
+    profile.apply(&app, &state);
+    info!("Device profile {} loaded", profile_name);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_device_profiles(app: tauri::AppHandle) -> Result<Vec<String>, BleError> {
+    let mut names = Vec::new();
+    for entry in std::fs::read_dir(profiles_dir(&app)?)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+            if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                names.push(stem.to_string());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/*
+    Current settings as a portable profile JSON, for sharing with other users.
+*/
+#[tauri::command]
+pub async fn export_device_profile(state: tauri::State<'_, AppState>) -> Result<String, BleError> {
+    let mut profile = DeviceProfile::capture("exported", &state);
+    // The connected device is specific to this installation
+    profile.device_address = None;
+    serde_json::to_string_pretty(&profile).map_err(|e| BleError::Io(format!("Serialize profile failed: {}", e)))
+}
+
+/*
+    Validate and apply a profile produced by export_device_profile.
+*/
+#[tauri::command]
+pub async fn import_device_profile(app: tauri::AppHandle, state: tauri::State<'_, AppState>, json: String) -> Result<(), BleError> {
+    let profile = parse_profile(&json)
+        .map_err(|e| BleError::InvalidArgument(format!("Parse profile failed: {}", e)))?;
+    profile.validate()?;
+    profile.apply(&app, &state);
+    info!("Device profile {} imported", profile.name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Profile saved before the version field existed
+    const V0_PROFILE: &str = r#"{
+        "name": "legacy",
+        "service_uuid": "0000ffe0-0000-1000-8000-00805f9b34fb",
+        "characteristics": [],
+        "axis_encoding": [
+            { "width": 2, "endian": "little" },
+            { "width": 2, "endian": "little" },
+            { "width": 2, "endian": "little" }
+        ]
+    }"#;
+
+    #[test]
+    fn v0_profile_migrates_to_current_version() {
+        let profile = parse_profile(V0_PROFILE).expect("v0 profile should migrate");
+        assert_eq!(profile.version, CURRENT_VERSION);
+        assert_eq!(profile.name, "legacy");
+        assert_eq!(profile.data_endianness, None);
+        assert_eq!(profile.heartbeat_characteristic, None);
+    }
+
+    // Version 1 profile listing the characteristic UUIDs, including the heartbeat
+    const V1_PROFILE: &str = r#"{
+        "version": 1,
+        "name": "listed",
+        "service_uuid": "0000ffe0-0000-1000-8000-00805f9b34fb",
+        "characteristics": [
+            ["x_axis", "00811914-4511-1919-1919-4511e3d7a9a1"],
+            ["heartbeat", "00811914-4511-1919-1919-4511000000be"]
+        ],
+        "axis_encoding": [
+            { "width": 2, "endian": "little" },
+            { "width": 2, "endian": "little" },
+            { "width": 2, "endian": "little" }
+        ]
+    }"#;
+
+    #[test]
+    fn v1_profile_keeps_heartbeat_characteristic() {
+        let profile = parse_profile(V1_PROFILE).expect("v1 profile should migrate");
+        assert_eq!(profile.version, CURRENT_VERSION);
+        assert_eq!(profile.heartbeat_characteristic, Some(crate::heartbeat::HEARTBEAT_CHARACTERISTIC_UUID));
+    }
+
+    #[test]
+    fn captured_default_profile_is_valid() {
+        assert!(DeviceProfile::capture("default", &AppState::default()).validate().is_ok());
+    }
+
+    #[test]
+    fn out_of_range_settings_are_rejected() {
+        let valid = DeviceProfile::capture("default", &AppState::default());
+
+        let mut profile = valid.clone();
+        profile.axis_encoding[0].width = MAXIUM_AXIS_WIDTH + 1;
+        assert!(matches!(profile.validate(), Err(BleError::InvalidArgument(_))));
+
+        let mut profile = valid.clone();
+        profile.joystick_frame_rate = Some(MAX_JOYSTICK_FRAME_RATE + 1);
+        assert!(matches!(profile.validate(), Err(BleError::InvalidArgument(_))));
+
+        let mut profile = valid;
+        profile.disconnect_after_ms = Some(0);
+        assert!(matches!(profile.validate(), Err(BleError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn heartbeat_clashing_with_a_characteristic_is_rejected() {
+        let mut profile = DeviceProfile::capture("default", &AppState::default());
+        profile.heartbeat_characteristic = Some(crate::X_CHARACTERISTIC_UUID);
+        assert!(matches!(profile.validate(), Err(BleError::InvalidUuid(_))));
+    }
+
+    #[test]
+    fn future_profile_version_is_rejected() {
+        let future = V0_PROFILE.replacen('{', &format!("{{ \"version\": {},", CURRENT_VERSION + 1), 1);
+        let error = parse_profile(&future).expect_err("future profile version should be rejected");
+        assert!(error.contains("newer than supported"), "{}", error);
+    }
+}
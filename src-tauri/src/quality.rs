@@ -0,0 +1,240 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use serde::Serialize;
+use tauri::Manager;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::session::SessionTracker;
+use crate::disconnect::{record_disconnect, DisconnectReason};
+use crate::queue::STALLED_PENDING_WRITES;
+use crate::{events, lock, rate_limit, reconnect, AppState};
+
+// Weights of the score components, sum to 1.0
+const RSSI_WEIGHT: f32 = 0.4;
+const RELIABILITY_WEIGHT: f32 = 0.4;
+const LATENCY_WEIGHT: f32 = 0.2;
+// RSSI mapped linearly from -100 dBm (0.0) to -40 dBm (1.0)
+const RSSI_FLOOR_DBM: f32 = -100.0;
+const RSSI_CEIL_DBM: f32 = -40.0;
+// Write latency giving a latency score of 0.5
+const REFERENCE_LATENCY_US: f64 = 50_000.0;
+// Consecutive monitor ticks a backlog above STALLED_PENDING_WRITES has to go without draining
+const STALLED_TICKS: u32 = 3;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionQuality {
+    pub score: f32,
+    pub rssi: Option<i16>,
+    pub avg_latency_us: f64,
+    pub error_rate: f32,
+    pub rating: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct QualityUpdate {
+    score: f32,
+    rating: String,
+}
+
+fn rating_for(score: f32) -> &'static str {
+    if score >= 80.0 {
+        "excellent"
+    } else if score >= 60.0 {
+        "good"
+    } else if score >= 40.0 {
+        "fair"
+    } else {
+        "poor"
+    }
+}
+
+/*
+    Combine RSSI, error rate and write latency of the current session into a 0..=100 score.
+    Missing RSSI counts as average signal.
+*/
+pub(crate) fn compute_quality(session: &SessionTracker) -> ConnectionQuality {
+    let rssi_score = session
+        .last_rssi
+        .map(|rssi| ((rssi as f32 - RSSI_FLOOR_DBM) / (RSSI_CEIL_DBM - RSSI_FLOOR_DBM)).clamp(0.0, 1.0))
+        .unwrap_or(0.5);
+    let error_rate = session.error_rate();
+    let avg_latency_us = session.avg_latency_us();
+    let latency_score = (1.0 / (1.0 + avg_latency_us / REFERENCE_LATENCY_US)) as f32;
+
+    let score = 100.0 * (RSSI_WEIGHT * rssi_score + RELIABILITY_WEIGHT * (1.0 - error_rate) + LATENCY_WEIGHT * latency_score);
+
+    ConnectionQuality {
+        score,
+        rssi: session.last_rssi,
+        avg_latency_us,
+        error_rate,
+        rating: rating_for(score).to_string(),
+    }
+}
+
+/*
+    Watchdog state of the quality monitor. A stall is a backlog above STALLED_PENDING_WRITES that did not shrink
+    for STALLED_TICKS ticks, a queue draining slowly under a low write rate limit is not one.
+    Fires once per stall and re-arms when the backlog is back at the threshold.
+*/
+#[derive(Debug, Default)]
+struct StallDetector {
+    last_pending: i64,
+    stalled_ticks: u32,
+    fired: bool,
+}
+
+impl StallDetector {
+    // Returns true on the tick a stall is detected
+    fn observe(&mut self, pending: i64) -> bool {
+        let draining = pending < self.last_pending;
+        self.last_pending = pending;
+        if pending <= STALLED_PENDING_WRITES {
+            self.stalled_ticks = 0;
+            self.fired = false;
+            return false;
+        }
+        if draining {
+            self.stalled_ticks = 0;
+            return false;
+        }
+        self.stalled_ticks = self.stalled_ticks.saturating_add(1);
+        if self.fired || self.stalled_ticks < STALLED_TICKS {
+            return false;
+        }
+        self.fired = true;
+        true
+    }
+}
+
+/*
+    Drop and restore a stalled link. Disconnects first so connect does not run on a link that is still up.
+*/
+async fn restart_stalled_link(state: &AppState) -> Result<(), BleError> {
+    let handler = state.handler()?;
+    reconnect::expect_disconnect(state, true);
+    if let Err(e) = handler.disconnect().await {
+        reconnect::expect_disconnect(state, false);
+        let message = format!("Disconnect stalled link failed: {}", e);
+        record_disconnect(state, DisconnectReason::Error(message.clone()));
+        return Err(BleError::Command(message));
+    }
+    record_disconnect(state, DisconnectReason::Timeout);
+    reconnect::reconnect_with_backoff(state).await
+}
+
+/*
+    Read the RSSI of the connected device into the session, the score and adapt_to_rssi use the latest sample.
+*/
+async fn sample_rssi(state: &AppState) -> Result<(), BleError> {
+    let device = state
+        .handler()?
+        .connected_device()
+        .await
+        .map_err(|e| BleError::Command(format!("Read RSSI failed: {}", e)))?;
+    if let Some(rssi) = device.rssi {
+        lock(&state.session).record_rssi(rssi);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_connection_quality(state: tauri::State<'_, AppState>) -> Result<ConnectionQuality, BleError> {
+    crate::ensure_connected(&state).await?;
+    if let Err(e) = sample_rssi(&state).await {
+        info!("get_connection_quality: {}", e);
+    }
+    Ok(compute_quality(&lock(&state.session)))
+}
+
+/*
+    Emit ble://quality-update every `interval_ms` while connected.
+    Also acts as connection watchdog: a backlog that stays above STALLED_PENDING_WRITES triggers one reconnect, see StallDetector.
+*/
+#[tauri::command]
+pub async fn start_quality_monitor(app: tauri::AppHandle, state: tauri::State<'_, AppState>, interval_ms: u64) -> Result<String, String> {
+    if interval_ms == 0 {
+        return Err("Interval must be greater than 0".to_string());
+    }
+
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+        let mut stall = StallDetector::default();
+        loop {
+            interval.tick().await;
+            let state = app.state::<AppState>();
+            if !*lock(&state.is_connected) {
+                continue;
+            }
+            if let Err(e) = sample_rssi(&state).await {
+                info!("Quality monitor: {}", e);
+            }
+            let quality = compute_quality(&lock(&state.session));
+            rate_limit::adapt_to_rssi(&state, quality.rssi);
+            events::emit("ble://quality-update", QualityUpdate {
+                score: quality.score,
+                rating: quality.rating,
+            });
+
+            let pending = state.pending_write_count.load(Ordering::SeqCst);
+            if stall.observe(pending) {
+                info!("{} writes pending for {} ticks, connection looks stalled, reconnecting", pending, STALLED_TICKS);
+                if let Err(e) = restart_stalled_link(&state).await {
+                    info!("Watchdog reconnect failed: {}", e);
+                }
+            }
+        }
+    });
+
+    crate::tasks::track(&state, "quality_monitor", &handle);
+    if let Some(previous) = lock(&state.quality_monitor).replace(handle) {
+        previous.abort();
+    }
+    Ok(format!("Quality monitor started with {} ms interval", interval_ms))
+}
+
+#[tauri::command]
+pub async fn stop_quality_monitor(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    if let Some(handle) = lock(&state.quality_monitor).take() {
+        handle.abort();
+    }
+    Ok("Quality monitor stopped.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stall_needs_several_ticks_without_draining() {
+        let mut stall = StallDetector::default();
+        let backlog = STALLED_PENDING_WRITES + 5;
+        for _ in 1..STALLED_TICKS {
+            assert!(!stall.observe(backlog));
+        }
+        assert!(stall.observe(backlog));
+    }
+
+    #[test]
+    fn draining_backlog_is_not_a_stall() {
+        let mut stall = StallDetector::default();
+        let mut pending = STALLED_PENDING_WRITES + 20;
+        for _ in 0..10 {
+            assert!(!stall.observe(pending));
+            pending -= 1;
+        }
+    }
+
+    #[test]
+    fn one_stall_fires_once_until_rearmed() {
+        let mut stall = StallDetector::default();
+        let backlog = STALLED_PENDING_WRITES + 5;
+        let fired = (0..10).filter(|_| stall.observe(backlog)).count();
+        assert_eq!(fired, 1);
+
+        assert!(!stall.observe(0));
+        let fired = (0..10).filter(|_| stall.observe(backlog)).count();
+        assert_eq!(fired, 1);
+    }
+}
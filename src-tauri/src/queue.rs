@@ -0,0 +1,236 @@
+use std::sync::atomic::Ordering;
+use std::time::{Duration, SystemTime};
+use serde::Serialize;
+use tauri::Manager;
+use tokio::sync::mpsc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::error::BleError;
+use crate::events::{self, report};
+use crate::policy::WritePolicy;
+use crate::session::unix_millis;
+use crate::{ensure_connected, ensure_writes_allowed, lock, AppState};
+
+// Maximum number of writes waiting for or in flight to the device
+pub const WRITE_QUEUE_CAPACITY: usize = 32;
+// Pending writes above which the connection is considered stalled
+pub const STALLED_PENDING_WRITES: i64 = 10;
+// Writes buffered for the background writer, newer writes are dropped beyond this
+const BACKGROUND_WRITE_CAPACITY: usize = 16;
+// Snapshots kept in the queue depth history unless changed by set_queue_stats_capacity
+pub const DEFAULT_QUEUE_STATS_CAPACITY: usize = 100;
+const QUEUE_STATS_INTERVAL_MS: u64 = 1000;
+// Fill ratios at which ble://queue-pressure-high and ble://queue-pressure-normal are emitted
+const PRESSURE_HIGH_RATIO: f32 = 0.8;
+const PRESSURE_NORMAL_RATIO: f32 = 0.5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueStatus {
+    pub depth: usize,
+    pub capacity: usize,
+    pub fill_ratio: f32,
+    pub is_full: bool,
+}
+
+impl QueueStatus {
+    fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            capacity: WRITE_QUEUE_CAPACITY,
+            fill_ratio: depth as f32 / WRITE_QUEUE_CAPACITY as f32,
+            is_full: depth >= WRITE_QUEUE_CAPACITY,
+        }
+    }
+}
+
+fn update_pressure(state: &AppState, depth: usize) {
+    let status = QueueStatus::new(depth);
+    if status.fill_ratio > PRESSURE_HIGH_RATIO {
+        if !state.queue_pressure_high.swap(true, Ordering::SeqCst) {
+            events::emit("ble://queue-pressure-high", status);
+        }
+    } else if status.fill_ratio < PRESSURE_NORMAL_RATIO
+        && state.queue_pressure_high.compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst).is_ok()
+    {
+        events::emit("ble://queue-pressure-normal", status);
+    }
+}
+
+/*
+    Slot of the write queue, released when dropped.
+    Writes beyond the capacity are rejected instead of piling up behind the rate limiter.
+*/
+pub(crate) struct QueueSlot<'a>(&'a AppState);
+
+impl<'a> QueueSlot<'a> {
+    pub(crate) fn acquire(state: &'a AppState) -> Result<Self, BleError> {
+        let depth = state.write_queue_depth.fetch_add(1, Ordering::SeqCst) + 1;
+        if depth > WRITE_QUEUE_CAPACITY {
+            state.write_queue_depth.fetch_sub(1, Ordering::SeqCst);
+            lock(&state.session).dropped_frames += 1;
+            return Err(report(BleError::WriteQueueFull(WRITE_QUEUE_CAPACITY)));
+        }
+        update_pressure(state, depth);
+        Ok(Self(state))
+    }
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        let depth = self.0.write_queue_depth.fetch_sub(1, Ordering::SeqCst) - 1;
+        update_pressure(self.0, depth);
+    }
+}
+
+/*
+    Counts a write_data call in AppState::pending_write_count until it returns, whatever the outcome.
+    Unlike QueueSlot it also covers writes rejected before reaching the queue.
+*/
+pub(crate) struct PendingWrite<'a>(&'a AppState);
+
+impl<'a> PendingWrite<'a> {
+    pub(crate) fn start(state: &'a AppState) -> Self {
+        state.pending_write_count.fetch_add(1, Ordering::SeqCst);
+        Self(state)
+    }
+}
+
+impl Drop for PendingWrite<'_> {
+    fn drop(&mut self) {
+        self.0.pending_write_count.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub struct BackgroundWrite {
+    char_uuid: Uuid,
+    service: Uuid,
+    data: Vec<u8>,
+}
+
+/*
+    Sender of the background writer, the writer task is started on first use.
+*/
+fn background_sender(app: &tauri::AppHandle, state: &AppState) -> mpsc::Sender<BackgroundWrite> {
+    let mut sender = lock(&state.background_write_tx);
+    if let Some(tx) = sender.as_ref().filter(|tx| !tx.is_closed()) {
+        return tx.clone();
+    }
+
+    let (tx, mut rx) = mpsc::channel::<BackgroundWrite>(BACKGROUND_WRITE_CAPACITY);
+    let app = app.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        while let Some(write) = rx.recv().await {
+            let state = app.state::<AppState>();
+            if let Err(e) = ensure_writes_allowed(&state) {
+                info!("Background write skipped: {}", e);
+                continue;
+            }
+            if let Err(e) = crate::write_data_as(state, write.char_uuid, write.service, write.data, WritePolicy::best_effort()).await {
+                info!("Background write failed: {}", e);
+            }
+        }
+    });
+    crate::tasks::track(state, "background_writer", &handle);
+    if let Some(previous) = lock(&state.background_write_task).replace(handle) {
+        previous.abort();
+    }
+    *sender = Some(tx.clone());
+    tx
+}
+
+/*
+    Hand the write to the background writer and return without waiting for the device.
+    If the writer is behind the write is dropped and counted in dropped_frames.
+    The writer uses the best effort write policy (without response, short timeout).
+*/
+#[tauri::command]
+pub async fn write_data_fire_and_forget(app: tauri::AppHandle, state: tauri::State<'_, AppState>, char_uuid: Uuid, service: Uuid, data: Vec<u8>) -> Result<(), BleError> {
+    ensure_connected(&state).await?;
+    ensure_writes_allowed(&state)?;
+
+    if background_sender(&app, &state).try_send(BackgroundWrite { char_uuid, service, data }).is_err() {
+        lock(&state.session).dropped_frames += 1;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_queue_depth(state: tauri::State<'_, AppState>) -> Result<QueueStatus, String> {
+    Ok(QueueStatus::new(state.write_queue_depth.load(Ordering::SeqCst)))
+}
+
+/*
+    A count that keeps growing means writes are not draining, e.g. the BLE stack stalled.
+*/
+#[tauri::command]
+pub async fn get_pending_write_count(state: tauri::State<'_, AppState>) -> Result<i64, String> {
+    Ok(state.pending_write_count.load(Ordering::SeqCst))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueSnapshot {
+    pub timestamp_ms: u64,
+    pub depth: usize,
+    pub dropped_last_second: u64,
+}
+
+/*
+    Snapshot the write queue depth and dropped writes every second into AppState::queue_depth_history.
+    Started once from setup().
+*/
+pub(crate) fn start_queue_sampler(app: tauri::AppHandle) {
+    let sampler_app = app.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(QUEUE_STATS_INTERVAL_MS));
+        let mut last_dropped = 0;
+        loop {
+            interval.tick().await;
+            let state = sampler_app.state::<AppState>();
+            // Counters restart on reset_connection_stats, saturate instead of going negative
+            let dropped = lock(&state.session).dropped_frames;
+            let snapshot = QueueSnapshot {
+                timestamp_ms: unix_millis(SystemTime::now()),
+                depth: state.write_queue_depth.load(Ordering::SeqCst),
+                dropped_last_second: dropped.saturating_sub(last_dropped),
+            };
+            last_dropped = dropped;
+
+            let capacity = *lock(&state.queue_stats_capacity);
+            let mut history = lock(&state.queue_depth_history);
+            history.push_back(snapshot);
+            while history.len() > capacity {
+                history.pop_front();
+            }
+        }
+    });
+    crate::tasks::track(&app.state::<AppState>(), "queue_stats_sampler", &handle);
+    let previous = lock(&app.state::<AppState>().queue_stats_task).replace(handle);
+    if let Some(previous) = previous {
+        previous.abort();
+    }
+}
+
+#[tauri::command]
+pub async fn get_queue_stats(state: tauri::State<'_, AppState>) -> Result<Vec<QueueSnapshot>, String> {
+    Ok(lock(&state.queue_depth_history).iter().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn reset_queue_stats(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    lock(&state.queue_depth_history).clear();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_queue_stats_capacity(state: tauri::State<'_, AppState>, capacity: usize) -> Result<String, String> {
+    if capacity == 0 {
+        return Err("Capacity must be greater than 0".to_string());
+    }
+    *lock(&state.queue_stats_capacity) = capacity;
+    let mut history = lock(&state.queue_depth_history);
+    while history.len() > capacity {
+        history.pop_front();
+    }
+    Ok(format!("Queue stats capacity set to: {} snapshots", capacity))
+}
@@ -0,0 +1,248 @@
+use std::time::{Duration, Instant, SystemTime};
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::session::unix_millis;
+use crate::{events, lock, AppState};
+
+// Signal below this is considered poor and slows writes down
+pub const POOR_RSSI_THRESHOLD_DBM: i16 = -80;
+// Rate divisor applied while the signal is poor
+const POOR_SIGNAL_RATE_DIVISOR: u32 = 4;
+// Upper bounds of the write and joystick frame rates, a BLE link cannot carry more
+pub const MAX_WRITE_RATE: u32 = 1000;
+pub const MAX_JOYSTICK_FRAME_RATE: u32 = 1000;
+
+/*
+    Token bucket limiting writes per second, a rate of 0 disables limiting.
+    `effective_rate` is the rate currently applied, it differs from `writes_per_second`
+    while adaptive limiting has slowed down writes.
+*/
+#[derive(Debug)]
+pub struct RateLimiter {
+    pub writes_per_second: u32,
+    pub effective_rate: u32,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RateAdapted {
+    writes_per_second: u32,
+    reason: String,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            writes_per_second: 0,
+            effective_rate: 0,
+            tokens: 0.0,
+            last_refill: Instant::now(),
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn set_rate(&mut self, writes_per_second: u32) {
+        self.writes_per_second = writes_per_second;
+        self.set_effective_rate(writes_per_second);
+    }
+
+    fn set_effective_rate(&mut self, rate: u32) {
+        self.effective_rate = rate;
+        self.tokens = self.tokens.min(rate as f64);
+        self.last_refill = Instant::now();
+    }
+
+    /*
+        Take one token, returning how long the caller has to wait before writing.
+    */
+    fn reserve(&mut self) -> Duration {
+        if self.effective_rate == 0 {
+            return Duration::ZERO;
+        }
+        let rate = self.effective_rate as f64;
+        let now = Instant::now();
+        self.tokens = (self.tokens + now.duration_since(self.last_refill).as_secs_f64() * rate).min(rate);
+        self.last_refill = now;
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+}
+
+/*
+    Token bucket for joystick frames, refilled by a background interval task.
+    Unlike RateLimiter, frames without a token are dropped instead of delayed, stale input is worthless.
+    A rate of 0 disables the throttle.
+*/
+#[derive(Debug, Default)]
+pub struct FrameThrottle {
+    pub frames_per_second: u32,
+    tokens: u32,
+}
+
+impl FrameThrottle {
+    fn refill(&mut self) {
+        self.tokens = (self.tokens + 1).min(self.frames_per_second);
+    }
+
+    fn try_take(&mut self) -> bool {
+        if self.frames_per_second == 0 {
+            return true;
+        }
+        if self.tokens == 0 {
+            return false;
+        }
+        self.tokens -= 1;
+        true
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FrameDropped {
+    reason: &'static str,
+    x: u8,
+    y: u8,
+    r: u8,
+    queued_at_ms: u64,
+}
+
+/*
+    Take a token for a joystick frame. Returns false if the frame has to be dropped,
+    the drop is counted in dropped_frames and emitted as ble://frame-dropped if enabled.
+*/
+pub(crate) fn take_frame(state: &AppState, x: u8, y: u8, r: u8) -> bool {
+    if lock(&state.frame_throttle).try_take() {
+        return true;
+    }
+    lock(&state.session).dropped_frames += 1;
+    if *lock(&state.emit_drop_events) {
+        let queued_at_ms = unix_millis(SystemTime::now());
+        events::emit("ble://frame-dropped", FrameDropped { reason: "rate_limit", x, y, r, queued_at_ms });
+    }
+    false
+}
+
+/*
+    Wait until the rate limiter allows the next write.
+*/
+pub(crate) async fn throttle(state: &AppState) {
+    let wait = lock(&state.rate_limiter).reserve();
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/*
+    Slow writes down to a quarter of the configured rate while RSSI is below the threshold
+    and restore the configured rate once it recovers. No-op unless adaptive limiting is enabled.
+*/
+pub(crate) fn adapt_to_rssi(state: &AppState, rssi: Option<i16>) {
+    if !*lock(&state.adaptive_rate_limit) {
+        return;
+    }
+    let Some(rssi) = rssi else {
+        return;
+    };
+
+    let mut limiter = lock(&state.rate_limiter);
+    let (target, reason) = if rssi < POOR_RSSI_THRESHOLD_DBM {
+        ((limiter.writes_per_second / POOR_SIGNAL_RATE_DIVISOR).max(1), format!("RSSI {} dBm below {} dBm", rssi, POOR_RSSI_THRESHOLD_DBM))
+    } else {
+        (limiter.writes_per_second, format!("RSSI {} dBm recovered", rssi))
+    };
+
+    if limiter.writes_per_second == 0 || limiter.effective_rate == target {
+        return;
+    }
+    limiter.set_effective_rate(target);
+    drop(limiter);
+
+    events::emit("ble://rate-adapted", RateAdapted {
+        writes_per_second: target,
+        reason,
+    });
+}
+
+/*
+    Limit writes to `writes_per_second`, 0 disables the limit.
+*/
+#[tauri::command]
+pub async fn set_write_rate_limit(state: tauri::State<'_, AppState>, writes_per_second: u32) -> Result<String, String> {
+    if writes_per_second > MAX_WRITE_RATE {
+        return Err(format!("Write rate limit must be at most {} writes/s", MAX_WRITE_RATE));
+    }
+    lock(&state.rate_limiter).set_rate(writes_per_second);
+    let rssi = lock(&state.session).last_rssi;
+    adapt_to_rssi(&state, rssi);
+    Ok(format!("Write rate limit set to: {} writes/s", writes_per_second))
+}
+
+/*
+    Limit joystick frames to `frames_per_second`, excess frames are dropped. 0 disables the limit.
+*/
+#[tauri::command]
+pub async fn set_joystick_frame_rate(app: tauri::AppHandle, state: tauri::State<'_, AppState>, frames_per_second: u32) -> Result<String, String> {
+    if frames_per_second > MAX_JOYSTICK_FRAME_RATE {
+        return Err(format!("Joystick frame rate must be at most {} frames/s", MAX_JOYSTICK_FRAME_RATE));
+    }
+    set_frame_rate(app, &state, frames_per_second);
+    Ok(format!("Joystick frame rate limit set to: {} frames/s", frames_per_second))
+}
+
+/*
+    Store the frame rate and restart the frame_refill task, shared with profile loading.
+    Clamped to MAX_JOYSTICK_FRAME_RATE, a higher rate would round the refill period down to 0.
+*/
+pub(crate) fn set_frame_rate(app: tauri::AppHandle, state: &AppState, frames_per_second: u32) {
+    let frames_per_second = frames_per_second.min(MAX_JOYSTICK_FRAME_RATE);
+    {
+        let mut throttle = lock(&state.frame_throttle);
+        throttle.frames_per_second = frames_per_second;
+        throttle.tokens = frames_per_second;
+    }
+
+    let handle = (frames_per_second > 0).then(|| {
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / frames_per_second as f64));
+            loop {
+                interval.tick().await;
+                lock(&app.state::<AppState>().frame_throttle).refill();
+            }
+        })
+    });
+    if let Some(handle) = &handle {
+        crate::tasks::track(state, "frame_refill", handle);
+    }
+    if let Some(previous) = std::mem::replace(&mut *lock(&state.frame_refill_task), handle) {
+        previous.abort();
+    }
+}
+
+/*
+    Emit ble://frame-dropped for every frame dropped by the joystick frame rate limit. Off by default to avoid flooding.
+*/
+#[tauri::command]
+pub async fn set_emit_drop_events(state: tauri::State<'_, AppState>, enabled: bool) -> Result<String, String> {
+    *lock(&state.emit_drop_events) = enabled;
+    Ok(format!("Frame drop events set to: {}", enabled))
+}
+
+#[tauri::command]
+pub async fn set_adaptive_rate_limit(state: tauri::State<'_, AppState>, enabled: bool) -> Result<String, String> {
+    *lock(&state.adaptive_rate_limit) = enabled;
+    if enabled {
+        let rssi = lock(&state.session).last_rssi;
+        adapt_to_rssi(&state, rssi);
+    } else {
+        let mut limiter = lock(&state.rate_limiter);
+        let rate = limiter.writes_per_second;
+        limiter.set_effective_rate(rate);
+    }
+    Ok(format!("Adaptive rate limit set to: {}", enabled))
+}
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_blec::OnDisconnectHandler;
+use tracing::info;
+
+use crate::disconnect::{record_disconnect, DisconnectReason};
+use crate::error::BleError;
+use crate::connection_mode::ConnectionMode;
+use crate::{events, lock, AppState};
+
+const RECONNECT_MAX_ATTEMPTS: u8 = 3;
+const RECONNECT_BASE_DELAY_MS: u64 = 500;
+
+/*
+    What happens when the link drops without being disconnected by the app.
+    Custom runs a callback instead, it can only be set from Rust.
+*/
+#[derive(Clone, Default)]
+pub enum ReconnectStrategy {
+    #[default]
+    None,
+    AutoOnce,
+    AutoWithBackoff { max_attempts: u8, base_delay_ms: u64 },
+    // ? Arc instead of Box so the callback can be handed to the plugin on every connect
+    Custom(Arc<dyn Fn() + Send + Sync>),
+}
+
+/*
+    Serializable form of ReconnectStrategy for set_reconnect_strategy, e.g.
+    { "type": "auto_with_backoff", "max_attempts": 5, "base_delay_ms": 200 }
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReconnectStrategyConfig {
+    None,
+    AutoOnce,
+    AutoWithBackoff { max_attempts: u8, base_delay_ms: u64 },
+}
+
+impl ReconnectStrategy {
+    // Attempts and first delay of reconnect_with_backoff, strategies without their own use the defaults
+    fn backoff(&self) -> (u8, u64) {
+        match self {
+            Self::AutoOnce => (1, 0),
+            Self::AutoWithBackoff { max_attempts, base_delay_ms } => (*max_attempts, *base_delay_ms),
+            Self::None | Self::Custom(_) => (RECONNECT_MAX_ATTEMPTS, RECONNECT_BASE_DELAY_MS),
+        }
+    }
+}
+
+impl From<ReconnectStrategyConfig> for ReconnectStrategy {
+    fn from(config: ReconnectStrategyConfig) -> Self {
+        match config {
+            ReconnectStrategyConfig::None => Self::None,
+            ReconnectStrategyConfig::AutoOnce => Self::AutoOnce,
+            ReconnectStrategyConfig::AutoWithBackoff { max_attempts, base_delay_ms } => Self::AutoWithBackoff { max_attempts, base_delay_ms },
+        }
+    }
+}
+
+/*
+    Mark the next disconnect as done by the app so the reconnect strategy ignores it.
+*/
+pub(crate) fn expect_disconnect(state: &AppState, expected: bool) {
+    state.expected_disconnect.store(expected, Ordering::SeqCst);
+}
+
+/*
+    Clear is_connected after an unexpected drop whatever the strategy, then run the strategy.
+    The session stays open (connected_address is kept) so a reconnect can restore it.
+*/
+fn on_link_dropped() {
+    let Some(app) = events::app_handle() else {
+        return;
+    };
+    let state = app.state::<AppState>();
+    if state.expected_disconnect.swap(false, Ordering::SeqCst) || state.is_shutting_down.load(Ordering::SeqCst) {
+        return;
+    }
+    // ! On demand mode expects the link to be down between writes, ensure_link restores it
+    if *lock(&state.connection_mode) == ConnectionMode::OnDemand {
+        return;
+    }
+    *lock(&state.is_connected) = false;
+    record_disconnect(&state, DisconnectReason::LinkLoss);
+
+    let strategy = lock(&state.reconnect_strategy).clone();
+    match strategy {
+        ReconnectStrategy::None => {}
+        ReconnectStrategy::Custom(callback) => callback(),
+        ReconnectStrategy::AutoOnce | ReconnectStrategy::AutoWithBackoff { .. } => {
+            let task_app = app.clone();
+            let handle = tauri::async_runtime::spawn(async move {
+                if let Err(e) = reconnect_with_backoff(&task_app.state::<AppState>()).await {
+                    info!("Reconnect after link loss failed: {}", e);
+                }
+            });
+            crate::tasks::track(&state, "reconnect", &handle);
+        }
+    }
+}
+
+/*
+    Disconnect handler passed to the plugin on every connect.
+    Installed for every strategy so is_connected is cleared even when the app does not reconnect.
+*/
+pub(crate) fn on_disconnect_handler(state: &AppState) -> OnDisconnectHandler {
+    expect_disconnect(state, false);
+    OnDisconnectHandler::Sync(Box::new(on_link_dropped))
+}
+
+/*
+    Reconnect to the last connected address, doubling the delay after every failed attempt.
+    Attempts and first delay come from the reconnect strategy.
+    Does not scan first, the device is expected to still be in range.
+*/
+pub(crate) async fn reconnect_with_backoff(state: &AppState) -> Result<(), BleError> {
+    let address = lock(&state.connected_address).clone().ok_or(BleError::NotConnected)?;
+
+    let handler = state.handler()?;
+
+    let (max_attempts, base_delay_ms) = lock(&state.reconnect_strategy).backoff();
+    let mut delay = Duration::from_millis(base_delay_ms);
+    for attempt in 1..=max_attempts {
+        info!("Reconnecting to {} (attempt {}/{})...", address, attempt, max_attempts);
+        match handler.connect(&address, on_disconnect_handler(state), false).await {
+            Ok(_) => {
+                *lock(&state.is_connected) = true;
+                let mut session = lock(&state.session);
+                session.reconnect_count = session.reconnect_count.saturating_add(1);
+                info!("Reconnected to {}", address);
+                return Ok(());
+            }
+            Err(e) => {
+                info!("Reconnect attempt {} failed: {}", attempt, e);
+                if attempt < max_attempts {
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+            }
+        }
+    }
+
+    *lock(&state.is_connected) = false;
+    Err(BleError::ReconnectFailed(address))
+}
+
+/*
+    Set what happens when the link drops unexpectedly, applies to the current link too.
+*/
+#[tauri::command]
+pub async fn set_reconnect_strategy(state: tauri::State<'_, AppState>, strategy: ReconnectStrategyConfig) -> Result<String, BleError> {
+    if let ReconnectStrategyConfig::AutoWithBackoff { max_attempts: 0, .. } = strategy {
+        return Err(BleError::InvalidArgument("max_attempts must be greater than 0".to_string()));
+    }
+    info!("Reconnect strategy set to: {:?}", strategy);
+    *lock(&state.reconnect_strategy) = strategy.clone().into();
+    Ok(format!("Reconnect strategy set to: {:?}", strategy))
+}
+
+/*
+    When enabled, a write that fails because the link dropped triggers a reconnect and is replayed once.
+*/
+#[tauri::command]
+pub async fn set_auto_reconnect_on_write_fail(state: tauri::State<'_, AppState>, enabled: bool) -> Result<String, String> {
+    *lock(&state.auto_reconnect_on_write_fail) = enabled;
+    Ok(format!("Auto reconnect on write failure set to: {}", enabled))
+}
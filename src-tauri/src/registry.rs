@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::session::format_timestamp;
+use crate::{events, lock, AppState};
+
+/*
+    Device the app has connected to before, persisted in $APP_DATA_DIR/known_devices.json.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownDevice {
+    pub address: String,
+    pub alias: Option<String>,
+    pub last_connected: Option<String>,
+}
+
+// Known devices keyed by uppercase address
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceRegistry {
+    pub devices: BTreeMap<String, KnownDevice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LastDevice {
+    address: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DeviceForgotten {
+    address: String,
+}
+
+fn data_file(app: &tauri::AppHandle, name: &str) -> Result<PathBuf, BleError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| BleError::Io(format!("App data dir unavailable: {}", e)))?;
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join(name))
+}
+
+impl DeviceRegistry {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, BleError> {
+        let path = data_file(app, "known_devices.json")?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| BleError::Io(format!("Parse known devices failed: {}", e)))
+    }
+
+    pub fn save(&self, app: &tauri::AppHandle) -> Result<(), BleError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| BleError::Io(format!("Serialize known devices failed: {}", e)))?;
+        std::fs::write(data_file(app, "known_devices.json")?, json)?;
+        Ok(())
+    }
+
+    pub fn upsert(&mut self, address: &str, alias: Option<String>) -> &mut KnownDevice {
+        let key = address.to_uppercase();
+        let device = self.devices.entry(key.clone()).or_insert_with(|| KnownDevice {
+            address: key,
+            alias: None,
+            last_connected: None,
+        });
+        if alias.is_some() {
+            device.alias = alias;
+        }
+        device
+    }
+
+    pub fn remove(&mut self, address: &str) -> Option<KnownDevice> {
+        self.devices.remove(&address.to_uppercase())
+    }
+}
+
+/*
+    Remember a successful connection in the registry and as the last connected device.
+*/
+pub(crate) fn record_connection(app: &tauri::AppHandle, address: &str) {
+    let result = DeviceRegistry::load(app).and_then(|mut registry| {
+        registry.upsert(address, None).last_connected = Some(format_timestamp(SystemTime::now()));
+        registry.save(app)?;
+
+        let json = serde_json::to_string(&LastDevice { address: address.to_uppercase() })
+            .map_err(|e| BleError::Io(format!("Serialize last device failed: {}", e)))?;
+        std::fs::write(data_file(app, "last_device.json")?, json)?;
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        info!("Failed to record connection to {}: {}", address, e);
+    }
+}
+
+fn clear_last_device_if(app: &tauri::AppHandle, address: &str) -> Result<(), BleError> {
+    let path = data_file(app, "last_device.json")?;
+    if !path.exists() {
+        return Ok(());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    if let Ok(last) = serde_json::from_str::<LastDevice>(&content) {
+        if last.address.eq_ignore_ascii_case(address) {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_known_devices(app: tauri::AppHandle) -> Result<Vec<KnownDevice>, BleError> {
+    Ok(DeviceRegistry::load(&app)?.devices.into_values().collect())
+}
+
+#[tauri::command]
+pub async fn add_known_device(app: tauri::AppHandle, address: String, alias: Option<String>) -> Result<KnownDevice, BleError> {
+    let address = crate::normalize_mac_address(&address)?;
+    let mut registry = DeviceRegistry::load(&app)?;
+    let device = registry.upsert(&address, alias).clone();
+    registry.save(&app)?;
+    Ok(device)
+}
+
+/*
+    Disconnect from `address` (default: the connected device) and remove every trace of it:
+    registry entry, saved profiles and last_device.json.
+*/
+#[tauri::command]
+pub async fn disconnect_and_forget(app: tauri::AppHandle, state: tauri::State<'_, AppState>, address: Option<String>) -> Result<(), BleError> {
+    let connected = lock(&state.connected_address).clone();
+    let address = address.or_else(|| connected.clone()).ok_or(BleError::NotConnected)?;
+    let address = crate::normalize_mac_address(&address)?;
+
+    if connected.is_some_and(|addr| addr.eq_ignore_ascii_case(&address)) {
+        crate::disconnect(app.clone(), state).await?;
+    }
+
+    let mut registry = DeviceRegistry::load(&app)?;
+    registry.remove(&address);
+    registry.save(&app)?;
+    crate::profile::delete_profiles_for_device(&app, &address)?;
+    clear_last_device_if(&app, &address)?;
+
+    info!("Device {} forgotten", address);
+    events::emit("ble://device-forgotten", DeviceForgotten { address });
+    Ok(())
+}
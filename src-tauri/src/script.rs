@@ -0,0 +1,204 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::error::BleError;
+use crate::{lock, AppState};
+
+/*
+    One step of an arm script. Scripts are JSON arrays of steps, e.g.
+    [{ "type": "joystick", "x": 127, "y": 200, "r": 127 }, { "type": "wait", "ms": 500 }]
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ScriptStep {
+    Joystick { x: u8, y: u8, r: u8 },
+    Lift {
+        value: u8,
+        #[serde(default = "default_lift_channel")]
+        channel: String,
+    },
+    Claw { command: String },
+    Wait { ms: u64 },
+    Loop { count: u32, steps: Vec<ScriptStep> },
+}
+
+// Upper bound of the step count after loops are expanded
+const MAX_SCRIPT_STEPS: usize = 10_000;
+const CLAW_COMMANDS: [&str; 3] = ["grab", "release", "start"];
+
+fn default_lift_channel() -> String {
+    "A".to_string()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptResult {
+    pub steps_executed: usize,
+    pub total_steps: usize,
+    pub completed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptProgress {
+    pub current_step: usize,
+    pub total_steps: usize,
+}
+
+/*
+    Expand loops so progress can be reported against a flat step count.
+    Rejects unknown claw commands and scripts of more than MAX_SCRIPT_STEPS steps before anything runs.
+*/
+fn flatten_steps(steps: &[ScriptStep], out: &mut Vec<ScriptStep>) -> Result<(), BleError> {
+    for step in steps {
+        match step {
+            ScriptStep::Loop { count, steps } => {
+                let mut body = Vec::new();
+                flatten_steps(steps, &mut body)?;
+                if body.is_empty() {
+                    continue;
+                }
+                let total = (body.len() as u64).saturating_mul(*count as u64).saturating_add(out.len() as u64);
+                if total > MAX_SCRIPT_STEPS as u64 {
+                    return Err(BleError::InvalidArgument(format!("Script expands to more than {} steps", MAX_SCRIPT_STEPS)));
+                }
+                for _ in 0..*count {
+                    out.extend(body.iter().cloned());
+                }
+            }
+            ScriptStep::Claw { command } if !CLAW_COMMANDS.contains(&command.as_str()) => {
+                return Err(BleError::InvalidScript(format!("Unsupported claw command: {}", command)));
+            }
+            other => {
+                if out.len() >= MAX_SCRIPT_STEPS {
+                    return Err(BleError::InvalidArgument(format!("Script expands to more than {} steps", MAX_SCRIPT_STEPS)));
+                }
+                out.push(other.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn execute_step(state: tauri::State<'_, AppState>, step: &ScriptStep) -> Result<(), BleError> {
+    match step {
+        ScriptStep::Joystick { x, y, r } => {
+            crate::send_joystick_data(state, *x, *y, *r).await?;
+        }
+        ScriptStep::Lift { value, channel } => {
+            crate::send_lifting_arm_value(state, channel.clone(), *value).await?;
+        }
+        ScriptStep::Claw { command } => match command.as_str() {
+            "grab" => crate::claw::set_claw_closed(state, true).await?,
+            "release" => crate::claw::set_claw_closed(state, false).await?,
+            _ => {
+                crate::send_arm_command(state, command.clone()).await?;
+            }
+        },
+        ScriptStep::Wait { ms } => {
+            tokio::time::sleep(Duration::from_millis(*ms)).await;
+        }
+        ScriptStep::Loop { .. } => unreachable!("loops are flattened before execution"),
+    }
+    Ok(())
+}
+
+/*
+    Execute script steps sequentially, stop_script aborts before the next step.
+    `name` identifies the script in logs.
+*/
+pub(crate) async fn execute_steps(state: tauri::State<'_, AppState>, name: &str, steps: &[ScriptStep]) -> Result<ScriptResult, BleError> {
+    let mut flat = Vec::new();
+    flatten_steps(steps, &mut flat)?;
+
+    if state
+        .script_running
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err(BleError::ScriptAlreadyRunning);
+    }
+    state.script_stop.store(false, Ordering::SeqCst);
+    state.script_step.store(0, Ordering::SeqCst);
+    state.script_total_steps.store(flat.len(), Ordering::SeqCst);
+
+    info!("Running script {} ({} steps)", name, flat.len());
+
+    let mut result = Ok(());
+    let mut executed = 0;
+    for step in &flat {
+        if state.script_stop.load(Ordering::SeqCst) {
+            info!("Script {} stopped at step {}", name, executed);
+            break;
+        }
+        if let Err(e) = execute_step(state.clone(), step).await {
+            result = Err(e);
+            break;
+        }
+        executed += 1;
+        state.script_step.store(executed, Ordering::SeqCst);
+    }
+
+    state.script_running.store(false, Ordering::SeqCst);
+    result?;
+
+    Ok(ScriptResult {
+        steps_executed: executed,
+        total_steps: flat.len(),
+        completed: executed == flat.len(),
+    })
+}
+
+/*
+    Run a JSON arm script from `path`.
+*/
+#[tauri::command]
+pub async fn run_script(state: tauri::State<'_, AppState>, path: String) -> Result<ScriptResult, BleError> {
+    let content = std::fs::read_to_string(&path)?;
+    let steps: Vec<ScriptStep> = serde_json::from_str(&content)
+        .map_err(|e| BleError::InvalidScript(format!("{}: {}", path, e)))?;
+    execute_steps(state, &path, &steps).await
+}
+
+/*
+    Script run by preload_operation once the controller reports usable, e.g. a move to the home position.
+*/
+#[tauri::command]
+pub async fn set_on_ready_script(state: tauri::State<'_, AppState>, steps: Vec<ScriptStep>) -> Result<String, BleError> {
+    // Reject invalid scripts now rather than when the controller becomes usable
+    flatten_steps(&steps, &mut Vec::new())?;
+    let count = steps.len();
+    *lock(&state.on_ready_script) = Some(steps);
+    Ok(format!("On ready script set ({} steps).", count))
+}
+
+#[tauri::command]
+pub async fn clear_on_ready_script(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    *lock(&state.on_ready_script) = None;
+    Ok("On ready script cleared.".to_string())
+}
+
+/*
+    Run the on ready script if one is set.
+*/
+pub(crate) async fn run_on_ready_script(state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    let Some(steps) = lock(&state.on_ready_script).clone() else {
+        return Ok(());
+    };
+    execute_steps(state, "on_ready", &steps).await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_script(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    state.script_stop.store(true, Ordering::SeqCst);
+    Ok("Script stop requested.".to_string())
+}
+
+#[tauri::command]
+pub async fn get_script_progress(state: tauri::State<'_, AppState>) -> Result<ScriptProgress, String> {
+    Ok(ScriptProgress {
+        current_step: state.script_step.load(Ordering::SeqCst),
+        total_steps: state.script_total_steps.load(Ordering::SeqCst),
+    })
+}
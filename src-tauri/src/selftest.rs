@@ -0,0 +1,105 @@
+use std::sync::atomic::Ordering;
+use serde::Serialize;
+use tauri::Manager;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::queue::{QueueSlot, WRITE_QUEUE_CAPACITY};
+use crate::{events, lock, profile, AppState, MAXIUM_AXIS_WIDTH};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub passed: bool,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    pub all_passed: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SelfTestComplete {
+    passed: bool,
+}
+
+fn check(name: &str, result: Result<(), String>) -> CheckResult {
+    if let Err(e) = &result {
+        info!("Self test check {} failed: {}", name, e);
+    }
+    CheckResult {
+        name: name.to_string(),
+        passed: result.is_ok(),
+        detail: result.err(),
+    }
+}
+
+/*
+    Checked on the managed AppState, values the frontend may have changed must still be consistent.
+*/
+fn check_state(state: &AppState) -> Result<(), String> {
+    if *lock(&state.is_connected) && lock(&state.connected_address).is_none() {
+        return Err("Connected without a device address".to_string());
+    }
+    let (min, max) = *lock(&state.lifting_arm_range);
+    if min > max {
+        return Err(format!("Lifting arm range {}..={} is empty", min, max));
+    }
+    if let Some(encoding) = lock(&state.axis_encoding).iter().find(|encoding| encoding.width == 0 || encoding.width > MAXIUM_AXIS_WIDTH) {
+        return Err(format!("Unsupported axis data width: {}", encoding.width));
+    }
+    let depth = state.write_queue_depth.load(Ordering::SeqCst);
+    if depth > WRITE_QUEUE_CAPACITY {
+        return Err(format!("Write queue depth {} outside 0..={}", depth, WRITE_QUEUE_CAPACITY));
+    }
+    Ok(())
+}
+
+/*
+    Adaptive limiting only lowers the configured rate, and a configured limit never drops to unlimited.
+*/
+fn check_rate_limiter(state: &AppState) -> Result<(), String> {
+    let limiter = lock(&state.rate_limiter);
+    if limiter.effective_rate > limiter.writes_per_second {
+        return Err(format!("Effective rate {} above the configured {} writes per second", limiter.effective_rate, limiter.writes_per_second));
+    }
+    if limiter.writes_per_second != 0 && limiter.effective_rate == 0 {
+        return Err("Configured rate limit is not applied".to_string());
+    }
+    Ok(())
+}
+
+/*
+    Verify the app subsystems: BLE adapter, UUID constants, app state, write queue,
+    rate limiter and saved profiles. Failed checks are collected, not returned as errors.
+    Emits ble://self-test-complete with the overall result.
+*/
+pub(crate) fn self_test(app: &tauri::AppHandle) -> Result<SelfTestReport, BleError> {
+    let state = app
+        .try_state::<AppState>()
+        .ok_or_else(|| BleError::Command("App state is not managed".to_string()))?;
+
+    let checks = vec![
+        check("ble_adapter", state.handler().map(|_| ()).map_err(|e| e.to_string())),
+        check("uuids", crate::check_uuids()),
+        check("app_state", check_state(&state)),
+        check("write_queue", QueueSlot::acquire(&state).map(drop).map_err(|e| e.to_string())),
+        check("rate_limiter", check_rate_limiter(&state)),
+        check("profiles", profile::check_profiles(app).map(|_| ()).map_err(|e| e.to_string())),
+    ];
+
+    let all_passed = checks.iter().all(|check| check.passed);
+    info!("Self test finished, all passed: {}", all_passed);
+    events::emit("ble://self-test-complete", SelfTestComplete { passed: all_passed });
+    Ok(SelfTestReport { all_passed, checks })
+}
+
+/*
+    Run the self test again on the current state, also emitting ble://self-test-complete.
+*/
+#[tauri::command]
+pub async fn run_self_test(app: tauri::AppHandle) -> Result<SelfTestReport, BleError> {
+    self_test(&app)
+}
@@ -0,0 +1,112 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::events::report;
+use crate::{service_uuid, AppState};
+
+// Interval between confirmation reads of send_arm_command_confirmed
+const CONFIRMATION_POLL_MS: u64 = 50;
+
+/*
+    Arm command of a sequence step, e.g. { "type": "grab" } or { "type": "lift", "channel": "B", "value": 200 }
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ArmCommand {
+    Start,
+    Grab,
+    Release,
+    Claw { value: u8 },
+    Lift { channel: String, value: u8 },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArmSequenceStep {
+    pub command: ArmCommand,
+    pub delay_before_ms: Option<u64>,
+    pub delay_after_ms: Option<u64>,
+}
+
+async fn execute_command(state: tauri::State<'_, AppState>, command: &ArmCommand) -> Result<(), BleError> {
+    match command {
+        ArmCommand::Start => {
+            crate::send_arm_command(state, "start".to_string()).await?;
+        }
+        ArmCommand::Grab => {
+            crate::claw::set_claw_closed(state, true).await?;
+        }
+        ArmCommand::Release => {
+            crate::claw::set_claw_closed(state, false).await?;
+        }
+        ArmCommand::Claw { value } => {
+            crate::send_lifting_arm_value(state, "Claw".to_string(), *value).await?;
+        }
+        ArmCommand::Lift { channel, value } => {
+            crate::send_lifting_arm_value(state, channel.clone(), *value).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn delay(state: &AppState, ms: Option<u64>) -> Result<(), BleError> {
+    if let Some(ms) = ms {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+    }
+    if state.cancel_sequence.load(Ordering::SeqCst) {
+        return Err(BleError::SequenceCancelled);
+    }
+    Ok(())
+}
+
+/*
+    Run arm commands in order, e.g. grab-wait-release.
+    cancel_arm_sequence aborts before the next command.
+*/
+#[tauri::command]
+pub async fn send_arm_sequence(state: tauri::State<'_, AppState>, steps: Vec<ArmSequenceStep>) -> Result<(), BleError> {
+    crate::ensure_connected(&state).await?;
+    state.cancel_sequence.store(false, Ordering::SeqCst);
+
+    info!("Running arm sequence ({} steps)", steps.len());
+    for step in &steps {
+        delay(&state, step.delay_before_ms).await?;
+        execute_command(state.clone(), &step.command).await?;
+        delay(&state, step.delay_after_ms).await?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn cancel_arm_sequence(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    state.cancel_sequence.store(true, Ordering::SeqCst);
+    Ok("Arm sequence cancel requested.".to_string())
+}
+
+/*
+    Run an arm command, then read `confirmation_char` (alias or UUID) until it equals `expected`,
+    for devices that echo back the arm state. Fails with ConfirmationTimeout after `timeout_ms`.
+*/
+#[tauri::command]
+pub async fn send_arm_command_confirmed(state: tauri::State<'_, AppState>, command: ArmCommand, confirmation_char: String, expected: Vec<u8>, timeout_ms: u64) -> Result<(), BleError> {
+    crate::ensure_connected(&state).await?;
+    let char_uuid = crate::alias::resolve_characteristic(&state, &confirmation_char)?;
+
+    execute_command(state.clone(), &command).await?;
+
+    let confirmed = tokio::time::timeout(Duration::from_millis(timeout_ms), async {
+        loop {
+            match crate::receive_data(state.clone(), char_uuid, service_uuid(&state)).await {
+                Ok(data) if data == expected => return,
+                Ok(data) => info!("Waiting for confirmation on {}: got {:?}", confirmation_char, data),
+                Err(e) => info!("Confirmation read of {} failed: {}", confirmation_char, e),
+            }
+            tokio::time::sleep(Duration::from_millis(CONFIRMATION_POLL_MS)).await;
+        }
+    })
+    .await;
+
+    confirmed.map_err(|_| report(BleError::ConfirmationTimeout { characteristic: confirmation_char, timeout_ms }))
+}
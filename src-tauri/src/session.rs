@@ -0,0 +1,288 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::error::BleError;
+use crate::metrics::Metrics;
+use crate::{lock, AppState};
+
+/*
+    Statistics of one connection session, appended as a JSON line to $APP_LOG_DIR/sessions.jsonl.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub connected_at: String,
+    pub disconnected_at: String,
+    pub uptime_secs: u64,
+    pub total_writes: u64,
+    pub total_reads: u64,
+    pub write_errors: u64,
+    pub read_errors: u64,
+    pub dropped_frames: u64,
+    pub min_rssi: i16,
+    pub max_rssi: i16,
+    pub avg_rssi: f64,
+    pub reconnect_count: u8,
+}
+
+/*
+    Counters of the running session, kept in AppState::session.
+*/
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    connected_at: Option<SystemTime>,
+    pub total_writes: u64,
+    pub total_reads: u64,
+    pub write_errors: u64,
+    pub read_errors: u64,
+    pub dropped_frames: u64,
+    pub reconnect_count: u8,
+    rssi_min: Option<i16>,
+    rssi_max: Option<i16>,
+    rssi_sum: i64,
+    rssi_count: u64,
+    pub last_rssi: Option<i16>,
+    latency_sum_us: u64,
+    latency_count: u64,
+    stats_reset_at: Option<SystemTime>,
+    pub metrics: Metrics,
+}
+
+/*
+    Counters of the current stats epoch, returned by get_connection_stats.
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionStats {
+    pub write_count: u64,
+    pub read_count: u64,
+    pub error_count: u64,
+    pub dropped_frames: u64,
+    pub reconnect_attempts: u8,
+    pub avg_latency_us: f64,
+    pub stats_reset_at: String,
+}
+
+impl SessionTracker {
+    pub fn start(&mut self) {
+        *self = Self {
+            connected_at: Some(SystemTime::now()),
+            stats_reset_at: Some(SystemTime::now()),
+            ..Self::default()
+        };
+    }
+
+    // Zero all counters but keep the session running
+    pub fn reset_counters(&mut self) {
+        *self = Self {
+            connected_at: self.connected_at,
+            stats_reset_at: Some(SystemTime::now()),
+            ..Self::default()
+        };
+    }
+
+    pub fn stats(&self) -> ConnectionStats {
+        ConnectionStats {
+            write_count: self.total_writes,
+            read_count: self.total_reads,
+            error_count: self.write_errors + self.read_errors,
+            dropped_frames: self.dropped_frames,
+            reconnect_attempts: self.reconnect_count,
+            avg_latency_us: self.avg_latency_us(),
+            stats_reset_at: self.stats_reset_at.map(format_timestamp).unwrap_or_default(),
+        }
+    }
+
+    pub fn record_write(&mut self, success: bool) {
+        self.total_writes += 1;
+        if !success {
+            self.write_errors += 1;
+        }
+    }
+
+    pub fn record_read(&mut self, success: bool) {
+        self.total_reads += 1;
+        if !success {
+            self.read_errors += 1;
+        }
+    }
+
+    pub fn record_rssi(&mut self, rssi: i16) {
+        self.rssi_min = Some(self.rssi_min.map_or(rssi, |min| min.min(rssi)));
+        self.rssi_max = Some(self.rssi_max.map_or(rssi, |max| max.max(rssi)));
+        self.rssi_sum += rssi as i64;
+        self.rssi_count += 1;
+        self.last_rssi = Some(rssi);
+    }
+
+    pub fn record_latency(&mut self, latency: Duration) {
+        self.latency_sum_us += latency.as_micros() as u64;
+        self.latency_count += 1;
+    }
+
+    pub fn avg_latency_us(&self) -> f64 {
+        if self.latency_count == 0 {
+            0.0
+        } else {
+            self.latency_sum_us as f64 / self.latency_count as f64
+        }
+    }
+
+    // Fraction of failed reads and writes in this session
+    pub fn error_rate(&self) -> f32 {
+        let total = self.total_writes + self.total_reads;
+        if total == 0 {
+            0.0
+        } else {
+            (self.write_errors + self.read_errors) as f32 / total as f32
+        }
+    }
+
+    // Close the running session, returns None if no session was started
+    pub fn finish(&mut self) -> Option<SessionStats> {
+        let connected_at = self.connected_at.take()?;
+        let now = SystemTime::now();
+        Some(SessionStats {
+            connected_at: format_timestamp(connected_at),
+            disconnected_at: format_timestamp(now),
+            uptime_secs: now.duration_since(connected_at).map(|d| d.as_secs()).unwrap_or(0),
+            total_writes: self.total_writes,
+            total_reads: self.total_reads,
+            write_errors: self.write_errors,
+            read_errors: self.read_errors,
+            dropped_frames: self.dropped_frames,
+            min_rssi: self.rssi_min.unwrap_or(0),
+            max_rssi: self.rssi_max.unwrap_or(0),
+            avg_rssi: if self.rssi_count == 0 { 0.0 } else { self.rssi_sum as f64 / self.rssi_count as f64 },
+            reconnect_count: self.reconnect_count,
+        })
+    }
+}
+
+/*
+    Distribution of connection uptimes, returned by get_uptime_histogram.
+*/
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UptimeHistogram {
+    pub count: usize,
+    pub min_s: u64,
+    pub max_s: u64,
+    pub mean_s: f64,
+    pub p50_s: u64,
+    pub p90_s: u64,
+    pub p99_s: u64,
+}
+
+impl UptimeHistogram {
+    fn from_intervals(intervals: &[u64]) -> Self {
+        let mut sorted = intervals.to_vec();
+        sorted.sort_unstable();
+        let (Some(&min_s), Some(&max_s)) = (sorted.first(), sorted.last()) else {
+            return Self::default();
+        };
+        // Nearest-rank percentile
+        let percentile = |p: f64| sorted[((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len()) - 1];
+        Self {
+            count: sorted.len(),
+            min_s,
+            max_s,
+            mean_s: sorted.iter().sum::<u64>() as f64 / sorted.len() as f64,
+            p50_s: percentile(0.5),
+            p90_s: percentile(0.9),
+            p99_s: percentile(0.99),
+        }
+    }
+}
+
+pub(crate) fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/*
+    Format a system time as RFC 3339 UTC, e.g. 2025-01-31T08:30:00Z.
+*/
+pub(crate) fn format_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) as i64;
+    let (days, rem) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+
+    // Civil date from days since epoch (Howard Hinnant's algorithm)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, rem / 3600, rem % 3600 / 60, rem % 60
+    )
+}
+
+fn sessions_file(app: &tauri::AppHandle) -> Result<PathBuf, BleError> {
+    Ok(crate::logs::log_dir(app)?.join("sessions.jsonl"))
+}
+
+/*
+    Close the running session and append its statistics to sessions.jsonl.
+    Called on disconnect and when the app is closed.
+*/
+pub(crate) fn finish_session(app: &tauri::AppHandle, state: &AppState) {
+    let Some(stats) = lock(&state.session).finish() else {
+        return;
+    };
+    lock(&state.connection_intervals).push(stats.uptime_secs);
+
+    let result = sessions_file(app).and_then(|path| {
+        crate::logs::rotate_if_needed(state, &path);
+        let line = serde_json::to_string(&stats)
+            .map_err(|e| BleError::Io(format!("Serialize session failed: {}", e)))?;
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    });
+
+    match result {
+        Ok(()) => info!("Session stats saved: {:?}", stats),
+        Err(e) => info!("Failed to save session stats: {}", e),
+    }
+}
+
+#[tauri::command]
+pub async fn get_session_history(app: tauri::AppHandle, last_n: usize) -> Result<Vec<SessionStats>, BleError> {
+    let path = sessions_file(&app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    let sessions: Vec<SessionStats> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let skip = sessions.len().saturating_sub(last_n);
+    Ok(sessions.into_iter().skip(skip).collect())
+}
+
+#[tauri::command]
+pub async fn reset_connection_stats(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    lock(&state.session).reset_counters();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_connection_stats(state: tauri::State<'_, AppState>) -> Result<ConnectionStats, String> {
+    Ok(lock(&state.session).stats())
+}
+
+/*
+    Uptime distribution of the sessions closed since the app started, e.g. to spot links dropping every N seconds.
+*/
+#[tauri::command]
+pub async fn get_uptime_histogram(state: tauri::State<'_, AppState>) -> Result<UptimeHistogram, String> {
+    Ok(UptimeHistogram::from_intervals(&lock(&state.connection_intervals)))
+}
@@ -0,0 +1,142 @@
+use std::time::Duration;
+use serde::Serialize;
+use tauri::Manager;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::subscription;
+use crate::{ensure_connected, events, lock, AppState, service_uuid, CONTROLLER_USABLE, CONTROLLER_USABLE_CHARACTERISTIC_UUID};
+
+pub(crate) const DEFAULT_STATUS_POLL_INTERVAL_MS: u64 = 500;
+
+#[derive(Debug, Clone, Serialize)]
+struct ControllerStatusChanged {
+    usable: bool,
+}
+
+/*
+    Store the controller status and emit ble://controller-status-changed if it changed.
+    Device may send 1 byte (0x01) or 2 bytes ([0x00, 0x01]), the last byte holds the value.
+*/
+fn update_controller_status(state: &AppState, data: &[u8]) {
+    let usable = data.last() == Some(&CONTROLLER_USABLE);
+    let changed = {
+        let mut current = lock(&state.controller_usable);
+        let changed = *current != usable;
+        *current = usable;
+        changed
+    };
+    if changed {
+        info!("Controller status changed: {}", usable);
+        events::emit("ble://controller-status-changed", ControllerStatusChanged { usable });
+    }
+}
+
+fn start_status_polling(app: tauri::AppHandle, state: &AppState) {
+    let interval_ms = *lock(&state.controller_status_poll_interval_ms);
+    info!("Polling controller status every {} ms", interval_ms);
+
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+        loop {
+            interval.tick().await;
+            let Ok(handler) = app.state::<AppState>().handler() else {
+                continue;
+            };
+            if !*lock(&app.state::<AppState>().is_connected) {
+                continue;
+            }
+            let service = service_uuid(&app.state::<AppState>());
+            match handler.recv_data(CONTROLLER_USABLE_CHARACTERISTIC_UUID, Some(service)).await {
+                Ok(data) => update_controller_status(&app.state::<AppState>(), &data),
+                Err(e) => info!("Controller status poll failed: {}", e),
+            }
+        }
+    });
+
+    crate::tasks::track(state, "controller_status_poll", &handle);
+    if let Some(previous) = lock(&state.controller_status_task).replace(handle) {
+        previous.abort();
+    }
+}
+
+/*
+    Keep AppState::controller_usable in sync without frontend polling.
+    Subscribes to status notifications when enabled and supported, otherwise polls in the background.
+*/
+#[tauri::command]
+pub async fn enable_controller_status_notification(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    ensure_connected(&state).await?;
+
+    if *lock(&state.use_notification_if_available) {
+        // ? Registered like any other subscription so disconnect cleanup unsubscribes it
+        let subscribed = subscription::subscribe_with(
+            &app,
+            &state,
+            CONTROLLER_USABLE_CHARACTERISTIC_UUID,
+            service_uuid(&state),
+            "ble://notify/controller_usable".to_string(),
+            Some(update_controller_status),
+        )
+        .await;
+
+        match subscribed {
+            Ok(_) => {
+                info!("Subscribed to controller status notifications");
+                return Ok(());
+            }
+            Err(e) => info!("Controller status notifications unavailable ({}), falling back to polling", e),
+        }
+    }
+
+    start_status_polling(app, &state);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn disable_controller_status_notification(state: tauri::State<'_, AppState>) -> Result<(), BleError> {
+    if let Some(handle) = lock(&state.controller_status_task).take() {
+        handle.abort();
+    }
+    // ? Only subscribed when notifications were used, polling leaves nothing to unsubscribe
+    if lock(&state.subscriptions).contains_key(&CONTROLLER_USABLE_CHARACTERISTIC_UUID) {
+        subscription::unsubscribe(&state, CONTROLLER_USABLE_CHARACTERISTIC_UUID).await?;
+    }
+    // A dropped status subscription must not come back on reconnect
+    lock(&state.dropped_subscriptions).remove(&CONTROLLER_USABLE_CHARACTERISTIC_UUID);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_controller_status_strategy(state: tauri::State<'_, AppState>, use_notification_if_available: bool, poll_interval_ms: Option<u64>) -> Result<String, String> {
+    let interval_ms = poll_interval_ms.unwrap_or(DEFAULT_STATUS_POLL_INTERVAL_MS);
+    if interval_ms == 0 {
+        return Err("Poll interval must be greater than 0".to_string());
+    }
+    *lock(&state.use_notification_if_available) = use_notification_if_available;
+    *lock(&state.controller_status_poll_interval_ms) = interval_ms;
+    Ok(format!("Controller status strategy: notification={}, poll interval={} ms", use_notification_if_available, interval_ms))
+}
+
+/*
+    Debug builds only: report the controller as not usable for `duration_ms`, then usable again,
+    so the frontend error handling can be tested without hardware.
+*/
+#[cfg(debug_assertions)]
+#[tauri::command]
+pub async fn simulate_controller_not_usable(app: tauri::AppHandle, state: tauri::State<'_, AppState>, duration_ms: u64) -> Result<String, String> {
+    info!("Simulating controller not usable for {} ms", duration_ms);
+    update_controller_status(&state, &[crate::CONTROLLER_NOT_USABLE]);
+    let handle = tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(duration_ms)).await;
+        update_controller_status(&app.state::<AppState>(), &[CONTROLLER_USABLE]);
+    });
+    crate::tasks::track(&state, "simulate_controller_not_usable", &handle);
+    Ok(format!("Controller reported not usable for {} ms", duration_ms))
+}
+
+#[cfg(not(debug_assertions))]
+#[tauri::command]
+pub async fn simulate_controller_not_usable(_duration_ms: u64) -> Result<String, String> {
+    Err("simulate_controller_not_usable is only available in debug builds".to_string())
+}
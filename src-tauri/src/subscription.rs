@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::error::BleError;
+use crate::{ensure_connected, lock, AppState};
+
+/*
+    Active notification subscription, the received data is re-emitted as `event_name`.
+    `on_data` is called with every notification, before the notification threshold applies.
+*/
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub service: Uuid,
+    pub event_name: String,
+    pub on_data: Option<fn(&AppState, &[u8])>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SubscriptionDropped {
+    char_uuid: String,
+}
+
+pub(crate) fn parse_uuid(value: &str) -> Result<Uuid, BleError> {
+    Uuid::parse_str(value).map_err(|e| BleError::InvalidUuid(format!("{}: {}", value, e)))
+}
+
+/*
+    Whether a notification differs enough from the last emitted one.
+    A threshold of 0 passes every notification, values of a different length always pass.
+*/
+fn exceeds_threshold(last: Option<&Vec<u8>>, data: &[u8], min_change: u8) -> bool {
+    let Some(last) = last.filter(|_| min_change > 0) else {
+        return true;
+    };
+    last.len() != data.len() || last.iter().zip(data).any(|(old, new)| old.abs_diff(*new) > min_change)
+}
+
+pub(crate) async fn subscribe(app: &tauri::AppHandle, state: &AppState, char_uuid: Uuid, service: Uuid, event_name: String) -> Result<(), BleError> {
+    subscribe_with(app, state, char_uuid, service, event_name, None).await
+}
+
+/*
+    Like subscribe, also passing every notification to `on_data` inside the app.
+    Tracked in AppState::subscriptions so cleanup unsubscribes it and auto resubscribe restores it.
+*/
+pub(crate) async fn subscribe_with(app: &tauri::AppHandle, state: &AppState, char_uuid: Uuid, service: Uuid, event_name: String, on_data: Option<fn(&AppState, &[u8])>) -> Result<(), BleError> {
+    ensure_connected(state).await?;
+
+    let handler = state.handler()?;
+
+    let emitter = app.clone();
+    let event = event_name.clone();
+    // Last emitted value, the threshold is read on every notification so changes apply immediately
+    let last_emitted = std::sync::Mutex::new(None::<Vec<u8>>);
+    handler
+        .subscribe(char_uuid, Some(service), move |data: Vec<u8>| {
+            if let Some(on_data) = on_data {
+                on_data(&emitter.state::<AppState>(), &data);
+            }
+            let min_change = lock(&emitter.state::<AppState>().notification_thresholds).get(&char_uuid).copied().unwrap_or(0);
+            let mut last = lock(&last_emitted);
+            if !exceeds_threshold(last.as_ref(), &data, min_change) {
+                return;
+            }
+            *last = Some(data.clone());
+            let _ = emitter.emit(&event, data);
+        })
+        .await
+        .map_err(|e| BleError::SubscribeFailed(format!("{}: {}", char_uuid, e)))?;
+
+    info!("Subscribed to {} as {}", char_uuid, event_name);
+    lock(&state.subscriptions).insert(char_uuid, Subscription { service, event_name, on_data });
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn subscribe_to_characteristic(app: tauri::AppHandle, state: tauri::State<'_, AppState>, char_uuid: String, service_uuid: String, event_name: String) -> Result<(), BleError> {
+    let char_uuid = parse_uuid(&char_uuid)?;
+    let service = parse_uuid(&service_uuid)?;
+    subscribe(&app, &state, char_uuid, service, event_name).await
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubscriptionRequest {
+    pub char_uuid: String,
+    pub service_uuid: String,
+    pub event_name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionResult {
+    pub char_uuid: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/*
+    Subscribe to several characteristics in one call.
+    A failed subscription is recorded in its result and does not abort the remaining ones.
+*/
+#[tauri::command]
+pub async fn subscribe_to_characteristics(app: tauri::AppHandle, state: tauri::State<'_, AppState>, subscriptions: Vec<SubscriptionRequest>) -> Result<Vec<SubscriptionResult>, BleError> {
+    ensure_connected(&state).await?;
+
+    let mut results = Vec::with_capacity(subscriptions.len());
+    for request in subscriptions {
+        let outcome = match (parse_uuid(&request.char_uuid), parse_uuid(&request.service_uuid)) {
+            (Ok(char_uuid), Ok(service)) => subscribe(&app, &state, char_uuid, service, request.event_name).await,
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        };
+        results.push(SubscriptionResult {
+            char_uuid: request.char_uuid,
+            success: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+        });
+    }
+    Ok(results)
+}
+
+/*
+    Unsubscribe `char_uuid` and stop tracking it, so cleanup and auto resubscribe leave it alone.
+*/
+pub(crate) async fn unsubscribe(state: &AppState, char_uuid: Uuid) -> Result<(), BleError> {
+    let handler = state.handler()?;
+
+    handler
+        .unsubscribe(char_uuid)
+        .await
+        .map_err(|e| BleError::SubscribeFailed(format!("Unsubscribe {}: {}", char_uuid, e)))?;
+
+    lock(&state.subscriptions).remove(&char_uuid);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unsubscribe_from_characteristic(state: tauri::State<'_, AppState>, char_uuid: String) -> Result<(), BleError> {
+    let char_uuid = parse_uuid(&char_uuid)?;
+    unsubscribe(&state, char_uuid).await
+}
+
+/*
+    Unsubscribe all active subscriptions before the connection goes away.
+    Emits ble://subscription-dropped for each and keeps them for auto resubscribe.
+*/
+pub(crate) async fn cleanup_subscriptions(app: &tauri::AppHandle, state: &AppState) {
+    let active: HashMap<Uuid, Subscription> = std::mem::take(&mut *lock(&state.subscriptions));
+    if active.is_empty() {
+        return;
+    }
+
+    if let Ok(handler) = state.handler() {
+        for char_uuid in active.keys() {
+            if let Err(e) = handler.unsubscribe(*char_uuid).await {
+                info!("Failed to unsubscribe {}: {}", char_uuid, e);
+            }
+        }
+    }
+
+    for char_uuid in active.keys() {
+        let _ = app.emit("ble://subscription-dropped", SubscriptionDropped {
+            char_uuid: char_uuid.to_string(),
+        });
+    }
+
+    *lock(&state.dropped_subscriptions) = active;
+}
+
+/*
+    Re-subscribe to the subscriptions dropped by the last disconnect if auto resubscribe is enabled.
+*/
+pub(crate) async fn resubscribe_all(app: &tauri::AppHandle, state: &AppState) {
+    if !*lock(&state.start_auto_resubscribe) {
+        return;
+    }
+
+    let dropped: HashMap<Uuid, Subscription> = std::mem::take(&mut *lock(&state.dropped_subscriptions));
+    for (char_uuid, subscription) in dropped {
+        if let Err(e) = subscribe_with(app, state, char_uuid, subscription.service, subscription.event_name, subscription.on_data).await {
+            info!("Failed to resubscribe {}: {}", char_uuid, e);
+        }
+    }
+}
+
+/*
+    Suppress notifications of `char_uuid` unless a byte changed by more than `min_change` since the last emitted value.
+    0 passes every notification.
+*/
+#[tauri::command]
+pub async fn set_notification_threshold(state: tauri::State<'_, AppState>, char_uuid: String, min_change: u8) -> Result<String, BleError> {
+    let char_uuid = parse_uuid(&char_uuid)?;
+    let mut thresholds = lock(&state.notification_thresholds);
+    if min_change == 0 {
+        thresholds.remove(&char_uuid);
+    } else {
+        thresholds.insert(char_uuid, min_change);
+    }
+    Ok(format!("Notification threshold of {} set to: {}", char_uuid, min_change))
+}
+
+#[tauri::command]
+pub async fn set_auto_resubscribe(state: tauri::State<'_, AppState>, enabled: bool) -> Result<String, String> {
+    *lock(&state.start_auto_resubscribe) = enabled;
+    Ok(format!("Auto resubscribe set to: {}", enabled))
+}
@@ -0,0 +1,385 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant, SystemTime};
+use serde::Serialize;
+use tauri_plugin_blec::models::ScanFilter;
+use tokio::sync::mpsc;
+use tracing::info;
+
+use crate::error::BleError;
+use crate::events::{self, report};
+use crate::registry::DeviceRegistry;
+use crate::session::format_timestamp;
+use crate::{lock, service_uuid, AppState};
+
+// Extra time to wait for the last scan results after the scan window closed
+const SCAN_RESULT_GRACE_MS: u64 = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RssiSample {
+    pub address: String,
+    pub name: Option<String>,
+    pub rssi_samples: Vec<i16>,
+    pub avg_rssi: f64,
+    pub max_rssi: i16,
+    pub min_rssi: i16,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DeviceDiscoverable {
+    address: String,
+    elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BleDeviceInfo {
+    pub address: String,
+    pub name: Option<String>,
+    pub rssi: Option<i16>,
+    pub windows_seen: u8,
+    pub connected: bool,
+}
+
+/*
+    Nearby device returned by scan_and_list, merged with what the device registry knows about it.
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct EnrichedDevice {
+    pub address: String,
+    pub name: Option<String>,
+    pub rssi: Option<i16>,
+    pub last_seen: String,
+    pub is_known: bool,
+    pub alias: Option<String>,
+    // Advertises the active service UUID
+    pub is_meguru_device: bool,
+    pub last_connected: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeviceSort {
+    Rssi,
+    Name,
+    LastSeen,
+}
+
+impl DeviceSort {
+    fn parse(value: &str) -> Result<Self, BleError> {
+        match value {
+            "rssi" => Ok(Self::Rssi),
+            "name" => Ok(Self::Name),
+            "last_seen" => Ok(Self::LastSeen),
+            _ => Err(BleError::InvalidArgument(format!("Unsupported sort order: {}", value))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct MeguruDeviceFound {
+    address: String,
+    rssi: Option<i16>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ScanWindowComplete {
+    window: u8,
+    total_windows: u8,
+    devices_this_window: u32,
+}
+
+#[derive(Default)]
+struct DeviceSamples {
+    name: Option<String>,
+    rssi: Vec<i16>,
+}
+
+impl DeviceSamples {
+    fn into_sample(self, address: String) -> Option<RssiSample> {
+        let min_rssi = *self.rssi.iter().min()?;
+        let max_rssi = *self.rssi.iter().max()?;
+        let avg_rssi = self.rssi.iter().map(|&rssi| rssi as f64).sum::<f64>() / self.rssi.len() as f64;
+        Some(RssiSample {
+            address,
+            name: self.name,
+            rssi_samples: self.rssi,
+            avg_rssi,
+            max_rssi,
+            min_rssi,
+        })
+    }
+}
+
+/*
+    Scan for `duration_ms` and collect the RSSI of every advertisement per device, for site surveys.
+    Devices are sorted by average RSSI, strongest first. Devices without any RSSI reading are left out.
+*/
+#[tauri::command]
+pub async fn scan_rssi_map(state: tauri::State<'_, AppState>, duration_ms: u64) -> Result<Vec<RssiSample>, BleError> {
+    let handler = state.handler()?;
+    let _scan_guard = crate::claim_scanner(&state)?;
+
+    let (tx, mut rx) = mpsc::channel(10);
+    handler
+        .discover(Some(tx), duration_ms, ScanFilter::None, false)
+        .await
+        .map_err(|e| BleError::Command(format!("Scan failed: {}", e)))?;
+
+    let mut devices: HashMap<String, DeviceSamples> = HashMap::new();
+    let window = Duration::from_millis(duration_ms + SCAN_RESULT_GRACE_MS);
+    let _ = tokio::time::timeout(window, async {
+        while let Some(batch) = rx.recv().await {
+            for device in batch {
+                let entry = devices.entry(device.address.to_uppercase()).or_default();
+                if !device.name.is_empty() {
+                    entry.name = Some(device.name);
+                }
+                if let Some(rssi) = device.rssi {
+                    entry.rssi.push(rssi);
+                }
+            }
+        }
+    })
+    .await;
+
+    if let Err(e) = handler.stop_scan().await {
+        info!("scan_rssi_map: stop scan failed: {}", e);
+    }
+
+    let mut samples: Vec<RssiSample> = devices
+        .into_iter()
+        .filter_map(|(address, samples)| samples.into_sample(address))
+        .collect();
+    samples.sort_by(|a, b| b.avg_rssi.total_cmp(&a.avg_rssi));
+    Ok(samples)
+}
+
+/*
+    Scan until `address` shows up or `timeout_ms` expires, e.g. while the device is powering on.
+    Emits ble://device-discoverable when found. Does not connect.
+*/
+#[tauri::command]
+pub async fn wait_for_device_discoverable(state: tauri::State<'_, AppState>, address: String, timeout_ms: u64) -> Result<(), BleError> {
+    let address = crate::normalize_mac_address(&address)?;
+    let handler = state.handler()?;
+    let _scan_guard = crate::claim_scanner(&state)?;
+
+    let started = Instant::now();
+    let (tx, mut rx) = mpsc::channel(10);
+    handler
+        .discover(Some(tx), timeout_ms, ScanFilter::None, false)
+        .await
+        .map_err(|e| BleError::Command(format!("Scan failed: {}", e)))?;
+
+    let found = tokio::time::timeout(Duration::from_millis(timeout_ms), async {
+        while let Some(batch) = rx.recv().await {
+            if batch.iter().any(|device| device.address.eq_ignore_ascii_case(&address)) {
+                return true;
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false);
+
+    if let Err(e) = handler.stop_scan().await {
+        info!("wait_for_device_discoverable: stop scan failed: {}", e);
+    }
+
+    if !found {
+        return Err(report(BleError::ScanTimeout(address)));
+    }
+
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    info!("Device {} discoverable after {} ms", address, elapsed_ms);
+    events::emit("ble://device-discoverable", DeviceDiscoverable { address, elapsed_ms });
+    Ok(())
+}
+
+/*
+    Run `windows` consecutive scans of `window_ms` each and merge the devices by address,
+    keeping the best RSSI, so devices that advertise rarely are not missed.
+    Emits ble://scan-window-complete after every window.
+*/
+#[tauri::command]
+pub async fn multi_scan(state: tauri::State<'_, AppState>, windows: u8, window_ms: u64) -> Result<Vec<BleDeviceInfo>, BleError> {
+    if windows == 0 {
+        return Err(BleError::InvalidArgument("At least one scan window is required".to_string()));
+    }
+    let handler = state.handler()?;
+    let _scan_guard = crate::claim_scanner(&state)?;
+
+    let mut devices: HashMap<String, BleDeviceInfo> = HashMap::new();
+    for window in 1..=windows {
+        let (tx, mut rx) = mpsc::channel(10);
+        handler
+            .discover(Some(tx), window_ms, ScanFilter::None, false)
+            .await
+            .map_err(|e| BleError::Command(format!("Scan failed: {}", e)))?;
+
+        let mut seen_this_window: HashSet<String> = HashSet::new();
+        let _ = tokio::time::timeout(Duration::from_millis(window_ms + SCAN_RESULT_GRACE_MS), async {
+            while let Some(batch) = rx.recv().await {
+                for device in batch {
+                    let address = device.address.to_uppercase();
+                    let entry = devices.entry(address.clone()).or_insert_with(|| BleDeviceInfo {
+                        address: address.clone(),
+                        name: None,
+                        rssi: None,
+                        windows_seen: 0,
+                        connected: false,
+                    });
+                    if !device.name.is_empty() {
+                        entry.name = Some(device.name);
+                    }
+                    entry.rssi = entry.rssi.max(device.rssi);
+                    if seen_this_window.insert(address) {
+                        entry.windows_seen += 1;
+                    }
+                }
+            }
+        })
+        .await;
+
+        if let Err(e) = handler.stop_scan().await {
+            info!("multi_scan: stop scan failed: {}", e);
+        }
+
+        info!("multi_scan: window {}/{} saw {} device(s)", window, windows, seen_this_window.len());
+        events::emit("ble://scan-window-complete", ScanWindowComplete {
+            window,
+            total_windows: windows,
+            devices_this_window: seen_this_window.len() as u32,
+        });
+    }
+
+    Ok(sorted_with_connection(&state, devices))
+}
+
+/*
+    Strongest devices first, the connected device marked.
+*/
+fn sorted_with_connection(state: &AppState, devices: HashMap<String, BleDeviceInfo>) -> Vec<BleDeviceInfo> {
+    let connected_address = lock(&state.connected_address).clone();
+    let mut devices: Vec<BleDeviceInfo> = devices
+        .into_values()
+        .map(|device| BleDeviceInfo {
+            connected: connected_address.as_deref().is_some_and(|address| address.eq_ignore_ascii_case(&device.address)),
+            ..device
+        })
+        .collect();
+    devices.sort_by_key(|device| std::cmp::Reverse(device.rssi));
+    devices
+}
+
+/*
+    Scan for `timeout_ms` and return every device advertising the active service UUID, for setups with several robots.
+    Emits ble://meguru-device-found once per device.
+*/
+#[tauri::command]
+pub async fn scan_all_meguru_devices(state: tauri::State<'_, AppState>, timeout_ms: u64) -> Result<Vec<BleDeviceInfo>, BleError> {
+    let handler = state.handler()?;
+    let _scan_guard = crate::claim_scanner(&state)?;
+
+    let (tx, mut rx) = mpsc::channel(10);
+    handler
+        .discover(Some(tx), timeout_ms, ScanFilter::Service(service_uuid(&state)), false)
+        .await
+        .map_err(|e| BleError::Command(format!("Scan failed: {}", e)))?;
+
+    let mut devices: HashMap<String, BleDeviceInfo> = HashMap::new();
+    let _ = tokio::time::timeout(Duration::from_millis(timeout_ms + SCAN_RESULT_GRACE_MS), async {
+        while let Some(batch) = rx.recv().await {
+            for device in batch {
+                let address = device.address.to_uppercase();
+                let entry = devices.entry(address.clone()).or_insert_with(|| {
+                    events::emit("ble://meguru-device-found", MeguruDeviceFound { address: address.clone(), rssi: device.rssi });
+                    BleDeviceInfo {
+                        address,
+                        name: None,
+                        rssi: None,
+                        windows_seen: 1,
+                        connected: false,
+                    }
+                });
+                if !device.name.is_empty() {
+                    entry.name = Some(device.name);
+                }
+                entry.rssi = entry.rssi.max(device.rssi);
+            }
+        }
+    })
+    .await;
+
+    if let Err(e) = handler.stop_scan().await {
+        info!("scan_all_meguru_devices: stop scan failed: {}", e);
+    }
+
+    info!("scan_all_meguru_devices: {} device(s) found", devices.len());
+    Ok(sorted_with_connection(&state, devices))
+}
+
+/*
+    Scan for `timeout_ms` and list every nearby device with its registry alias and last connection.
+    `sort_by` is "rssi" (strongest first), "name" or "last_seen" (both ascending, unnamed devices last).
+    Returns an empty list when nothing was found.
+*/
+#[tauri::command]
+pub async fn scan_and_list(app: tauri::AppHandle, state: tauri::State<'_, AppState>, sort_by: String, timeout_ms: u64) -> Result<Vec<EnrichedDevice>, BleError> {
+    let sort = DeviceSort::parse(&sort_by)?;
+    let handler = state.handler()?;
+    let _scan_guard = crate::claim_scanner(&state)?;
+
+    let (tx, mut rx) = mpsc::channel(10);
+    handler
+        .discover(Some(tx), timeout_ms, ScanFilter::None, false)
+        .await
+        .map_err(|e| BleError::Command(format!("Scan failed: {}", e)))?;
+
+    let service = service_uuid(&state);
+    let registry = DeviceRegistry::load(&app)?;
+    let mut devices: HashMap<String, (EnrichedDevice, SystemTime)> = HashMap::new();
+    let _ = tokio::time::timeout(Duration::from_millis(timeout_ms + SCAN_RESULT_GRACE_MS), async {
+        while let Some(batch) = rx.recv().await {
+            let now = SystemTime::now();
+            for device in batch {
+                let address = device.address.to_uppercase();
+                let (entry, last_seen) = devices.entry(address.clone()).or_insert_with(|| {
+                    let known = registry.devices.get(&address);
+                    let device = EnrichedDevice {
+                        address,
+                        name: None,
+                        rssi: None,
+                        last_seen: String::new(),
+                        is_known: known.is_some(),
+                        alias: known.and_then(|known| known.alias.clone()),
+                        is_meguru_device: false,
+                        last_connected: known.and_then(|known| known.last_connected.clone()),
+                    };
+                    (device, now)
+                });
+                if !device.name.is_empty() {
+                    entry.name = Some(device.name);
+                }
+                entry.rssi = entry.rssi.max(device.rssi);
+                entry.is_meguru_device |= device.services.contains(&service);
+                *last_seen = now;
+            }
+        }
+    })
+    .await;
+
+    if let Err(e) = handler.stop_scan().await {
+        info!("scan_and_list: stop scan failed: {}", e);
+    }
+
+    let mut devices: Vec<(EnrichedDevice, SystemTime)> = devices.into_values().collect();
+    match sort {
+        DeviceSort::Rssi => devices.sort_by_key(|(device, _)| std::cmp::Reverse(device.rssi)),
+        DeviceSort::Name => devices.sort_by(|(a, _), (b, _)| (a.name.is_none(), &a.name).cmp(&(b.name.is_none(), &b.name))),
+        DeviceSort::LastSeen => devices.sort_by_key(|(_, last_seen)| *last_seen),
+    }
+    info!("scan_and_list: {} device(s) found", devices.len());
+    Ok(devices
+        .into_iter()
+        .map(|(device, last_seen)| EnrichedDevice { last_seen: format_timestamp(last_seen), ..device })
+        .collect())
+}
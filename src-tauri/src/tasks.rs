@@ -0,0 +1,89 @@
+use std::sync::Mutex;
+use std::time::SystemTime;
+use serde::Serialize;
+use tauri::async_runtime::JoinHandle;
+use tokio::task::AbortHandle;
+
+use crate::error::BleError;
+use crate::session::format_timestamp;
+use crate::{lock, AppState};
+
+// Tasks backing a setting, cancelling one would silently break it (e.g. no frame_refill drops every joystick frame)
+const INTERNAL_TASKS: [&str; 3] = ["frame_refill", "queue_stats_sampler", "background_writer"];
+
+/*
+    Background task registered in AppState::active_tasks, a restarted task replaces the entry of the same name.
+*/
+#[derive(Debug)]
+pub struct TaskHandle {
+    abort: AbortHandle,
+    started_at: SystemTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskInfo {
+    pub name: String,
+    pub started_at: String,
+    pub running: bool,
+}
+
+/*
+    Register a spawned task so it shows up in list_active_tasks and can be stopped with cancel_task.
+*/
+pub(crate) fn track<T>(state: &AppState, name: &str, handle: &JoinHandle<T>) {
+    let task = TaskHandle {
+        abort: handle.inner().abort_handle(),
+        started_at: SystemTime::now(),
+    };
+    lock(&state.active_tasks).insert(name.to_string(), task);
+}
+
+/*
+    Every background task spawned since startup, finished tasks are kept with running = false.
+*/
+#[tauri::command]
+pub async fn list_active_tasks(state: tauri::State<'_, AppState>) -> Result<Vec<TaskInfo>, String> {
+    let mut tasks: Vec<TaskInfo> = lock(&state.active_tasks)
+        .iter()
+        .map(|(name, task)| TaskInfo {
+            name: name.clone(),
+            started_at: format_timestamp(task.started_at),
+            running: !task.abort.is_finished(),
+        })
+        .collect();
+    tasks.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tasks)
+}
+
+/*
+    AppState slot keeping the JoinHandle of a cancellable task, cleared on cancel so it does not hold a dead handle.
+*/
+fn owning_slot<'a>(state: &'a AppState, name: &str) -> Option<&'a Mutex<Option<JoinHandle<()>>>> {
+    match name {
+        "claw_release" => Some(&state.claw_release_task),
+        "idle_disconnect" => Some(&state.idle_disconnect_task),
+        "heartbeat" => Some(&state.heartbeat_task),
+        "joystick_poll" => Some(&state.joystick_poll_task),
+        "controller_status_poll" => Some(&state.controller_status_task),
+        "quality_monitor" => Some(&state.quality_monitor),
+        _ => None,
+    }
+}
+
+/*
+    Abort a task listed by list_active_tasks. Internal tasks are refused, change their setting instead.
+*/
+#[tauri::command]
+pub async fn cancel_task(state: tauri::State<'_, AppState>, name: String) -> Result<String, BleError> {
+    if INTERNAL_TASKS.contains(&name.as_str()) {
+        return Err(BleError::InvalidArgument(format!("Task {} is internal and cannot be cancelled", name)));
+    }
+    let task = lock(&state.active_tasks)
+        .remove(&name)
+        .ok_or_else(|| BleError::InvalidArgument(format!("Unknown task: {}", name)))?;
+    task.abort.abort();
+    if let Some(slot) = owning_slot(&state, &name) {
+        lock(slot).take();
+    }
+    Ok(format!("Task {} cancelled", name))
+}
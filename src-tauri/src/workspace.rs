@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::BleError;
+use crate::{events, lock, AppState};
+
+/*
+    Safe range of every axis and the lifting arm, enforced while AppState::enforce_workspace is set.
+*/
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WorkspaceLimits {
+    pub x_min: u8,
+    pub x_max: u8,
+    pub y_min: u8,
+    pub y_max: u8,
+    pub r_min: u8,
+    pub r_max: u8,
+    pub lift_min: u8,
+    pub lift_max: u8,
+}
+
+impl Default for WorkspaceLimits {
+    fn default() -> Self {
+        Self {
+            x_min: 0,
+            x_max: u8::MAX,
+            y_min: 0,
+            y_max: u8::MAX,
+            r_min: 0,
+            r_max: u8::MAX,
+            lift_min: 0,
+            lift_max: u8::MAX,
+        }
+    }
+}
+
+impl WorkspaceLimits {
+    fn ranges(&self) -> [(&'static str, u8, u8); 4] {
+        [
+            ("x", self.x_min, self.x_max),
+            ("y", self.y_min, self.y_max),
+            ("r", self.r_min, self.r_max),
+            ("lift", self.lift_min, self.lift_max),
+        ]
+    }
+
+    fn validate(&self) -> Result<(), BleError> {
+        match self.ranges().into_iter().find(|(_, min, max)| min >= max) {
+            Some((axis, min, max)) => Err(BleError::InvalidArgument(format!("Workspace {} min {} must be below max {}", axis, min, max))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct WorkspaceClamped {
+    axis: String,
+    requested: u8,
+    clamped: u8,
+}
+
+/*
+    Clamp `value` of `axis` ("x", "y", "r" or "lift") to the workspace when enforcement is enabled.
+    Emits ble://workspace-clamped when the value changed.
+*/
+pub(crate) fn clamp_to_workspace(state: &AppState, axis: &str, value: u8) -> u8 {
+    if !*lock(&state.enforce_workspace) {
+        return value;
+    }
+    let limits = *lock(&state.workspace_limits);
+    let Some((_, min, max)) = limits.ranges().into_iter().find(|(name, _, _)| *name == axis) else {
+        return value;
+    };
+
+    let clamped = value.clamp(min, max);
+    if clamped != value {
+        events::emit("ble://workspace-clamped", WorkspaceClamped { axis: axis.to_string(), requested: value, clamped });
+    }
+    clamped
+}
+
+#[tauri::command]
+pub async fn set_workspace_limits(state: tauri::State<'_, AppState>, limits: WorkspaceLimits) -> Result<String, BleError> {
+    limits.validate()?;
+    *lock(&state.workspace_limits) = limits;
+    Ok(format!("Workspace limits set to {:?}", limits))
+}
+
+#[tauri::command]
+pub async fn get_workspace_limits(state: tauri::State<'_, AppState>) -> Result<WorkspaceLimits, String> {
+    Ok(*lock(&state.workspace_limits))
+}
+
+/*
+    Clamp send_joystick_data and send_lifting_arm_value to the workspace limits while enabled.
+*/
+#[tauri::command]
+pub async fn set_workspace_enforcement(state: tauri::State<'_, AppState>, enabled: bool) -> Result<String, String> {
+    *lock(&state.enforce_workspace) = enabled;
+    Ok(format!("Workspace enforcement: {}", enabled))
+}